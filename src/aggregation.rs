@@ -0,0 +1,120 @@
+use num_rational::BigRational;
+use num_traits::Zero;
+
+// The values of every state (group) in several columns, e.g. one per counterexample. Groups are
+// matched by name, as the groups can differ between columns (e.g. for optimistic responsibility,
+// only the states on the path are players). A group that does not occur in a column has value zero
+// in it.
+pub struct GroupTable {
+    group_names: Vec<String>,
+    values: Vec<Vec<BigRational>>, // Indexed by group and then by column
+    column_count: usize,
+}
+
+impl GroupTable {
+    pub fn new() -> Self {
+        Self {
+            group_names: Vec::new(),
+            values: Vec::new(),
+            column_count: 0,
+        }
+    }
+
+    pub fn add_column(&mut self, values: Vec<(String, BigRational)>) {
+        let column = self.column_count;
+        self.column_count += 1;
+        for group_values in &mut self.values {
+            group_values.push(BigRational::zero());
+        }
+        for (group_name, value) in values {
+            let group = match self.group_names.iter().position(|n| *n == group_name) {
+                Some(group) => group,
+                None => {
+                    self.group_names.push(group_name);
+                    self.values.push(vec![BigRational::zero(); column + 1]);
+                    self.group_names.len() - 1
+                }
+            };
+            self.values[group][column] += value;
+        }
+    }
+
+    pub fn column_count(&self) -> usize {
+        self.column_count
+    }
+
+    // Returns the name and the values of every group
+    pub fn rows(&self) -> impl Iterator<Item = (&str, &[BigRational])> {
+        self.group_names
+            .iter()
+            .map(String::as_str)
+            .zip(self.values.iter().map(Vec::as_slice))
+    }
+}
+
+// Collects the responsibility of every state (group) for several counterexamples, with one column
+// per counterexample
+pub struct AggregatedResponsibility {
+    counterexample_names: Vec<String>,
+    table: GroupTable,
+}
+
+pub struct GroupSummary<'a> {
+    pub name: &'a str,
+    pub mean: BigRational,
+    pub min: BigRational,
+    pub min_counterexample: &'a str, // The first counterexample with the minimum
+    pub max: BigRational,
+    pub max_counterexample: &'a str, // The first counterexample with the maximum
+}
+
+impl AggregatedResponsibility {
+    pub fn new() -> Self {
+        Self {
+            counterexample_names: Vec::new(),
+            table: GroupTable::new(),
+        }
+    }
+
+    pub fn add_counterexample(&mut self, name: String, values: Vec<(String, BigRational)>) {
+        self.counterexample_names.push(name);
+        self.table.add_column(values);
+    }
+
+    pub fn counterexample_count(&self) -> usize {
+        self.counterexample_names.len()
+    }
+
+    // Returns the summary of every group, sorted by decreasing mean responsibility
+    pub fn summaries(&self) -> Vec<GroupSummary<'_>> {
+        let count = BigRational::from_integer(self.counterexample_names.len().into());
+        let mut summaries = self
+            .table
+            .rows()
+            .map(|(name, values)| {
+                let mut min = 0;
+                let mut max = 0;
+                let mut sum = BigRational::zero();
+                for (counterexample, value) in values.iter().enumerate() {
+                    if *value < values[min] {
+                        min = counterexample;
+                    }
+                    if *value > values[max] {
+                        max = counterexample;
+                    }
+                    sum += value;
+                }
+                GroupSummary {
+                    name,
+                    mean: sum / &count,
+                    min: values[min].clone(),
+                    min_counterexample: &self.counterexample_names[min],
+                    max: values[max].clone(),
+                    max_counterexample: &self.counterexample_names[max],
+                }
+            })
+            .collect::<Vec<_>>();
+        summaries.sort_by(|x, y| y.mean.cmp(&x.mean));
+        summaries
+    }
+}
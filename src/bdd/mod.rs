@@ -0,0 +1,273 @@
+use num_bigint::BigInt;
+use num_traits::Zero;
+use std::collections::{HashMap, HashSet};
+
+// Reduced ordered binary decision diagrams over the variables 0..n, which are ordered by their
+// index. Nodes are hash-consed, so two functions are equal iff they are represented by the same
+// node.
+pub type BddNode = usize;
+
+pub const FALSE: BddNode = 0;
+pub const TRUE: BddNode = 1;
+
+const TERMINAL_VARIABLE: usize = usize::MAX;
+
+#[derive(Copy, Clone)]
+struct Node {
+    variable: usize,
+    low: BddNode,
+    high: BddNode,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Hash)]
+enum Operation {
+    And,
+    Or,
+}
+
+pub struct Bdd {
+    nodes: Vec<Node>,
+    unique_table: HashMap<(usize, BddNode, BddNode), BddNode>,
+    operation_cache: HashMap<(Operation, BddNode, BddNode), BddNode>,
+    not_cache: HashMap<BddNode, BddNode>,
+}
+
+impl Bdd {
+    pub fn new() -> Self {
+        let terminal = |value| Node {
+            variable: TERMINAL_VARIABLE,
+            low: value,
+            high: value,
+        };
+        Self {
+            nodes: vec![terminal(FALSE), terminal(TRUE)],
+            unique_table: HashMap::new(),
+            operation_cache: HashMap::new(),
+            not_cache: HashMap::new(),
+        }
+    }
+
+    // Returns the number of nodes reachable from the given node, including terminals
+    pub fn size(&self, node: BddNode) -> usize {
+        let mut visited = HashSet::new();
+        let mut open = vec![node];
+        while let Some(node) = open.pop() {
+            if visited.insert(node) && node != FALSE && node != TRUE {
+                open.push(self.nodes[node].low);
+                open.push(self.nodes[node].high);
+            }
+        }
+        visited.len()
+    }
+
+    pub fn variable(&mut self, variable: usize) -> BddNode {
+        self.make_node(variable, FALSE, TRUE)
+    }
+
+    fn make_node(&mut self, variable: usize, low: BddNode, high: BddNode) -> BddNode {
+        if low == high {
+            return low;
+        }
+        if let Some(&node) = self.unique_table.get(&(variable, low, high)) {
+            return node;
+        }
+        self.nodes.push(Node {
+            variable,
+            low,
+            high,
+        });
+        let node = self.nodes.len() - 1;
+        self.unique_table.insert((variable, low, high), node);
+        node
+    }
+
+    pub fn not(&mut self, node: BddNode) -> BddNode {
+        match node {
+            FALSE => return TRUE,
+            TRUE => return FALSE,
+            _ => {}
+        }
+        if let Some(&result) = self.not_cache.get(&node) {
+            return result;
+        }
+        let Node {
+            variable,
+            low,
+            high,
+        } = self.nodes[node];
+        let low = self.not(low);
+        let high = self.not(high);
+        let result = self.make_node(variable, low, high);
+        self.not_cache.insert(node, result);
+        result
+    }
+
+    pub fn and(&mut self, left: BddNode, right: BddNode) -> BddNode {
+        self.apply(Operation::And, left, right)
+    }
+
+    pub fn or(&mut self, left: BddNode, right: BddNode) -> BddNode {
+        self.apply(Operation::Or, left, right)
+    }
+
+    fn apply(&mut self, operation: Operation, left: BddNode, right: BddNode) -> BddNode {
+        match (operation, left, right) {
+            (Operation::And, FALSE, _) | (Operation::And, _, FALSE) => return FALSE,
+            (Operation::And, TRUE, other) | (Operation::And, other, TRUE) => return other,
+            (Operation::Or, TRUE, _) | (Operation::Or, _, TRUE) => return TRUE,
+            (Operation::Or, FALSE, other) | (Operation::Or, other, FALSE) => return other,
+            _ => {}
+        }
+        if left == right {
+            return left;
+        }
+        // Both operations are commutative, so the cache key is normalised
+        let key = (operation, left.min(right), left.max(right));
+        if let Some(&result) = self.operation_cache.get(&key) {
+            return result;
+        }
+
+        let left_node = self.nodes[left];
+        let right_node = self.nodes[right];
+        let variable = left_node.variable.min(right_node.variable);
+        let (left_low, left_high) = self.cofactors(left, variable);
+        let (right_low, right_high) = self.cofactors(right, variable);
+        let low = self.apply(operation, left_low, right_low);
+        let high = self.apply(operation, left_high, right_high);
+        let result = self.make_node(variable, low, high);
+        self.operation_cache.insert(key, result);
+        result
+    }
+
+    // Returns the functions obtained by setting `variable` to false and true, provided that the
+    // node does not depend on any variable before `variable`.
+    fn cofactors(&self, node: BddNode, variable: usize) -> (BddNode, BddNode) {
+        let node_data = self.nodes[node];
+        if node_data.variable == variable {
+            (node_data.low, node_data.high)
+        } else {
+            (node, node)
+        }
+    }
+
+    pub fn restrict(&mut self, node: BddNode, variable: usize, value: bool) -> BddNode {
+        let mut cache = HashMap::new();
+        self.restrict_cached(node, variable, value, &mut cache)
+    }
+
+    fn restrict_cached(
+        &mut self,
+        node: BddNode,
+        variable: usize,
+        value: bool,
+        cache: &mut HashMap<BddNode, BddNode>,
+    ) -> BddNode {
+        let node_data = self.nodes[node];
+        if node_data.variable > variable {
+            return node;
+        }
+        if node_data.variable == variable {
+            return if value { node_data.high } else { node_data.low };
+        }
+        if let Some(&result) = cache.get(&node) {
+            return result;
+        }
+        let low = self.restrict_cached(node_data.low, variable, value, cache);
+        let high = self.restrict_cached(node_data.high, variable, value, cache);
+        let result = self.make_node(node_data.variable, low, high);
+        cache.insert(node, result);
+        result
+    }
+
+    // Counts the satisfying assignments of the variables 0..variable_count by the number of
+    // variables they set to true. If a variable is excluded, the node must not depend on it, and it
+    // is left out of the assignments.
+    pub fn count_by_size(
+        &self,
+        node: BddNode,
+        variable_count: usize,
+        excluded_variable: Option<usize>,
+    ) -> Vec<BigInt> {
+        let mut cache = HashMap::new();
+        cache.insert(FALSE, vec![BigInt::zero()]);
+        cache.insert(TRUE, vec![BigInt::from(1)]);
+        self.count_from_node(node, variable_count, excluded_variable, &mut cache);
+        self.extended_counts(node, 0, variable_count, excluded_variable, &cache)
+    }
+
+    // Stores the counts of the node in the cache. The counts only consider the variables from the
+    // node's variable onwards.
+    fn count_from_node(
+        &self,
+        node: BddNode,
+        variable_count: usize,
+        excluded_variable: Option<usize>,
+        cache: &mut HashMap<BddNode, Vec<BigInt>>,
+    ) {
+        if cache.contains_key(&node) {
+            return;
+        }
+        let node_data = self.nodes[node];
+        self.count_from_node(node_data.low, variable_count, excluded_variable, cache);
+        self.count_from_node(node_data.high, variable_count, excluded_variable, cache);
+
+        let from = node_data.variable + 1;
+        let low = self.extended_counts(
+            node_data.low,
+            from,
+            variable_count,
+            excluded_variable,
+            cache,
+        );
+        let high = self.extended_counts(
+            node_data.high,
+            from,
+            variable_count,
+            excluded_variable,
+            cache,
+        );
+
+        let mut counts = low;
+        counts.resize(counts.len().max(high.len() + 1), BigInt::zero());
+        for (size, count) in high.into_iter().enumerate() {
+            counts[size + 1] += count;
+        }
+        cache.insert(node, counts);
+    }
+
+    // Returns the cached counts of the node, extended to all variables from `from` onwards
+    fn extended_counts(
+        &self,
+        node: BddNode,
+        from: usize,
+        variable_count: usize,
+        excluded_variable: Option<usize>,
+        cache: &HashMap<BddNode, Vec<BigInt>>,
+    ) -> Vec<BigInt> {
+        let to = self.nodes[node].variable.min(variable_count);
+        Self::add_free_variables(
+            cache[&node].clone(),
+            Self::free_variables(from, to, excluded_variable),
+        )
+    }
+
+    fn free_variables(from: usize, to: usize, excluded_variable: Option<usize>) -> usize {
+        match excluded_variable {
+            Some(excluded) if (from..to).contains(&excluded) => to - from - 1,
+            _ => to - from,
+        }
+    }
+
+    // Multiplies the counts by (1 + x)^free_variables, i.e. lets every free variable be either true
+    // or false.
+    fn add_free_variables(mut counts: Vec<BigInt>, free_variables: usize) -> Vec<BigInt> {
+        for _ in 0..free_variables {
+            counts.push(BigInt::zero());
+            for size in (1..counts.len()).rev() {
+                let (lower, upper) = counts.split_at_mut(size);
+                upper[0] += &lower[size - 1];
+            }
+        }
+        counts
+    }
+}
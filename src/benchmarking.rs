@@ -1,12 +1,17 @@
 use crate::cli::ResponsibilityVersion;
 use crate::prism::transition_system_parser::TransitionSystemParser;
 use crate::prism::{PrismInterface, PrismRunner, RunResults};
-use crate::shapley::{SampleTarget, StateGroups, TrimmedResponsibilityResult, WeightType};
+use crate::shapley::{
+    Estimator, SampleTarget, StateGroups, TrimmedResponsibilityResult, WeightType,
+};
 use comfy_table::{CellAlignment, Table};
 use num_rational::BigRational;
 use num_traits::{One, ToPrimitive};
 use std::time::Duration;
 
+// The trimmed results of one sampler run, together with the number of samples it drew
+type BenchmarkSample = (Vec<TrimmedResponsibilityResult>, usize);
+
 pub struct Benchmarker {
     benchmarks: Vec<Benchmark>,
     grouped: bool,
@@ -151,7 +156,7 @@ impl Benchmarker {
 
                     let mut squared_difference: f64 = 0.0;
                     for state in 0..state_count {
-                        squared_difference += (&reference_means[state]
+                        squared_difference += (reference_means[state]
                             - sample[state].total_value.to_f64().unwrap())
                         .powi(2)
                     }
@@ -204,12 +209,8 @@ impl Benchmarker {
         benchmark_index: usize,
         benchmark: &Benchmark,
         progress_bar: &mut indicatif::ProgressBar,
-    ) -> (
-        Vec<Vec<(Vec<TrimmedResponsibilityResult>, usize)>>,
-        usize,
-        Vec<f64>,
-    ) {
-        fastrand::seed(benchmark.seed as u64);
+    ) -> (Vec<Vec<BenchmarkSample>>, usize, Vec<f64>) {
+        fastrand::seed(benchmark.seed);
 
         let mut results = Vec::new();
         let mut samples_by_duration = Vec::new();
@@ -248,6 +249,7 @@ impl Benchmarker {
                     ResponsibilityVersion::Pessimistic,
                 );
                 responsibility_calculator.set_silent(true);
+                responsibility_calculator.set_seed(fastrand::u64(..));
 
                 let target = match &benchmark.sample_counts {
                     Some(counts) => {
@@ -256,10 +258,11 @@ impl Benchmarker {
                     None => SampleTarget::ElapsedTime(Duration::from_secs_f32(duration)),
                 };
 
-                let resp = responsibility_calculator.sample_individual_responsibilities(target);
+                let resp = responsibility_calculator
+                    .sample_individual_responsibilities(target, Estimator::Coalition);
                 samples.push((
                     resp.into_iter()
-                        .map(|r| TrimmedResponsibilityResult::from_responsibility_result(r))
+                        .map(TrimmedResponsibilityResult::from_responsibility_result)
                         .collect::<Vec<_>>(),
                     responsibility_calculator.sampled_count,
                 ));
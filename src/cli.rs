@@ -1,5 +1,9 @@
-use crate::shapley::{SampleTarget, WeightType};
+use crate::generator::GeneratorSettings;
+use crate::shapley::{Estimator, SampleTarget, WeightType};
 use clap::{Arg, ArgAction, Command, ValueHint};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Signed, Zero};
 use std::time::Duration;
 
 pub struct Settings {
@@ -11,10 +15,11 @@ pub struct Settings {
     pub no_prism: bool,
 }
 
-#[derive(Eq, PartialEq)]
+#[derive(Copy, Clone, Eq, PartialEq)]
 pub enum ResponsibilityVersion {
     Optimistic,
     Pessimistic,
+    Forward, // The game is played on the model alone, without a counterexample
 }
 
 pub struct PrismConfig {
@@ -22,24 +27,48 @@ pub struct PrismConfig {
     pub java_path: Option<String>,
 }
 
+#[allow(clippy::large_enum_variant)] // Only one subcommand is ever constructed
 pub enum Subcommand {
     Run(RunSubcommand),
     Benchmark(BenchmarkSubcommand),
+    Generate(GenerateSubcommand),
 }
 
 pub struct RunSubcommand {
     pub model_input: ModelInput,
     pub counterexample_input: CounterexampleInput,
     pub bad_label: Option<String>,
+    pub good_label: Option<String>,
+    pub infinitely_often: bool,
+    pub ltl: Option<String>,
+    pub within: Option<usize>,
+    pub shorten: bool,
+    pub check_runs: Option<usize>, // Set to compare the stochastic with the exact engine
     pub responsibility_output: ResponsibilityOutput,
     pub engine: Engine,
     pub grouped: bool,
+    pub seed: Option<u64>,
+    pub prune: bool,
+    pub exclude_dummies: bool,
+    pub symmetry: bool,
+    pub owen: bool,
+    pub groups_file: Option<String>,
+    pub group_variables: Vec<String>,
+    pub fix: bool,
+    pub costs: Vec<(String, BigRational)>, // The cost of controlling a state (group), by name
+    pub strategy_coalition: Option<Vec<String>>,
 }
 
 pub struct BenchmarkSubcommand {
     pub file: String,
 }
 
+pub struct GenerateSubcommand {
+    pub stem: String, // The generated files are "<stem>.sta", "<stem>.tra", etc.
+    pub bad_label: String,
+    pub settings: GeneratorSettings,
+}
+
 pub enum ModelInput {
     PrismFile {
         file: String,
@@ -87,7 +116,7 @@ impl ModelInput {
             };
             let counterexample = match counterexample {
                 CounterexampleInput::ModelChecker => CounterexampleInput::File {file: format!("{}.ce", stem.expect("Cannot use --no-prism when model input is already a preprocessed transition system and counterexample is not specified."))},
-                CounterexampleInput::File { file } => CounterexampleInput::File { file },
+                counterexample => counterexample,
             };
             (model, counterexample)
         }
@@ -97,6 +126,20 @@ impl ModelInput {
 pub enum CounterexampleInput {
     ModelChecker,
     File { file: String },
+    // The following inputs give several counterexamples, over which responsibility is aggregated
+    Files { files: Vec<String> },
+    AllShortest,
+    Random { count: usize },
+    Unused, // Forward responsibility is computed without a counterexample
+}
+
+impl CounterexampleInput {
+    pub fn is_aggregated(&self) -> bool {
+        match self {
+            Self::ModelChecker | Self::File { .. } | Self::Unused => false,
+            Self::Files { .. } | Self::AllShortest | Self::Random { .. } => true,
+        }
+    }
 }
 
 pub enum ResponsibilityOutput {
@@ -106,14 +149,16 @@ pub enum ResponsibilityOutput {
 
 pub enum Engine {
     Exact,
-    Stochastic(SampleTarget),
+    Antichain,
+    Symbolic,
+    Stochastic(SampleTarget, Estimator),
 }
 
 impl Engine {
     pub fn is_stochastic(&self) -> bool {
         match self {
-            Self::Exact => false,
-            Self::Stochastic(_) => true,
+            Self::Exact | Self::Antichain | Self::Symbolic => false,
+            Self::Stochastic(_, _) => true,
         }
     }
 }
@@ -132,13 +177,67 @@ impl Settings {
                     .num_args(1)
                     .value_hint(ValueHint::FilePath)
             )
+            .arg(
+                Arg::new("generate")
+                    .long("generate")
+                    .value_name("stem")
+                    .conflicts_with_all(["benchmark", "model-input-file", "state-file", "good-label", "ltl", "counterexample", "counterexamples"])
+                    .help("Generates a random transition system with a counterexample instead of computing responsibility, e.g. to study how the engines scale. The files \"<stem>.sta\", \"<stem>.tra\", \"<stem>.lab\" and \"<stem>.ce\" are written in the format of PRISM's exports, with the bad states labelled by --bad-label (\"bad\" by default). They can be used with --no-prism, also in benchmark files. Uses --seed.")
+                    .value_hint(ValueHint::FilePath)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("states")
+                    .long("states")
+                    .requires("generate")
+                    .conflicts_with_all(["benchmark", "model-input-file", "state-file"])
+                    .help("The number of states of the generated transition system. Defaults to 100.")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("branching")
+                    .long("branching")
+                    .requires("generate")
+                    .conflicts_with_all(["benchmark", "model-input-file", "state-file"])
+                    .help("The number of distinct successors of every state of the generated transition system that is not bad. Bad states only have a self-loop. Defaults to 2.")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("bad-density")
+                    .long("bad-density")
+                    .requires("generate")
+                    .conflicts_with_all(["benchmark", "model-input-file", "state-file"])
+                    .help("The fraction of states of the generated transition system that are bad. There is always at least one bad state. Defaults to 0.05.")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("ce-length")
+                    .long("ce-length")
+                    .requires("generate")
+                    .conflicts_with_all(["benchmark", "model-input-file", "state-file"])
+                    .help("The number of steps of the generated counterexample, which visits distinct states and only ends in a bad state. Defaults to 5.")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("labels")
+                    .long("labels")
+                    .requires("generate")
+                    .conflicts_with_all(["benchmark", "model-input-file", "state-file"])
+                    .help("The number of labels \"l1\", \"l2\", etc. of the generated transition system. Every state gets one of them at random, so they can be used with --grouped. If 0, the states have no labels besides \"init\" and the bad label. Defaults to 0.")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
             .arg(
                 Arg::new("model-input-file")
                     .long("prism-model")
                     .short('p')
                     .help("The prism input model")
                     .value_name("model.prism")
-                    .required_unless_present_any(&["state-file", "benchmark"])
+                    .required_unless_present_any(["state-file", "benchmark", "generate"])
                     .conflicts_with("benchmark")
                     .value_hint(ValueHint::FilePath)
                     .num_args(1)
@@ -148,16 +247,48 @@ impl Settings {
                     .long("bad-label")
                     .short('b')
                     .help("The label in the model that should be avoided")
-                    .required_unless_present_any(&["benchmark"])
+                    .required_unless_present_any(["benchmark", "good-label", "ltl", "generate"])
                     .conflicts_with("benchmark")
                     .value_hint(ValueHint::Other)
                     .num_args(1)
             )
+            .arg(
+                Arg::new("good-label")
+                    .long("good-label")
+                    .help("The label in the model that should eventually be reached. Instead of reaching a bad state, the counterexample is then a lasso (or a path into a state without successors) that never visits a state with this label. Without a counterexample file, the shortest such lasso is used.")
+                    .conflicts_with_all(["benchmark", "bad-label", "counterexamples"])
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("infinitely-often")
+                    .long("infinitely-often")
+                    .requires("good-label")
+                    .action(ArgAction::SetTrue)
+                    .help("If set, states with the good label must be visited infinitely often instead of only once. The cycle of the counterexample must then avoid them.")
+                    .num_args(0)
+            )
+            .arg(
+                Arg::new("ltl")
+                    .long("ltl")
+                    .help("An LTL formula that the model should satisfy, e.g. \"G(req -> F grant)\", instead of a bad label. Propositions are labels or comparisons \"variable=value\". The counterexample must be a lasso that violates the formula. Without a counterexample file, a shortest such lasso is used. Formulas in which G, R or W occurs within F or U (after moving negations inwards), e.g. \"F G a\", are not supported.")
+                    .conflicts_with_all(["benchmark", "bad-label", "good-label", "counterexamples"])
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("within")
+                    .long("within")
+                    .value_name("steps")
+                    .help("If set, the bad state must be reached within the given number of steps, e.g. to model a missed deadline. The coalition wins if it can avoid the bad states for that many steps. The counterexample must not be longer.")
+                    .conflicts_with_all(["benchmark", "good-label", "ltl"])
+                    .num_args(1)
+            )
             .arg(
                 Arg::new("metric")
                     .long("metric")
                     .short('m')
-                    .help("The metric for responsibility. Possible values: shapley, banzhaf, count")
+                    .help("The metric for responsibility. Possible values: shapley, banzhaf, count, deegan-packel, holler-packel, johnston, beta:<alpha>,<beta>, weighted-banzhaf:<p>, weights:<file>. Deegan-Packel, Holler-Packel and Johnston are only supported by the exact engine. A weight file contains one weight per coalition size, starting with size 1")
                     .default_value("shapley")
                     .value_hint(ValueHint::Other)
                     .num_args(1)
@@ -171,6 +302,80 @@ impl Settings {
                     .help("If set, states are grouped by labels, i.e. either all states with a given label are in the coalition or none of them are. Note that labels may overlap and that states with no labels are also allowed.")
                     .num_args(0)
             )
+            .arg(
+                Arg::new("groups")
+                    .long("groups")
+                    .conflicts_with("benchmark")
+                    .help("A file that defines custom groups of states, which replace the labels of the model. Each line gives the name of a group, a colon and either a list of states, e.g. \"(x=1,y=2) (x=1,y=3)\", or a predicate over the variables, e.g. \"sig1=red | sig2=red\". States are grouped by these groups, or, if --owen is set, the groups define the unions.")
+                    .value_hint(ValueHint::FilePath)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("group-by-var")
+                    .long("group-by-var")
+                    .conflicts_with_all(["benchmark", "groups"])
+                    .help("Groups the states by the value of the given variable, e.g. \"turn\" gives one group per value of turn. Several variables can be given separated by commas, in which case there is one group per combination of values. Like --groups, this replaces the labels of the model.")
+                    .value_hint(ValueHint::Other)
+                    .value_delimiter(',')
+                    .action(ArgAction::Append)
+            )
+            .arg(
+                Arg::new("prune")
+                    .long("prune")
+                    .conflicts_with("benchmark")
+                    .action(ArgAction::SetTrue)
+                    .help("If set, states that are unreachable from the initial state are removed and states that cannot reach a bad state are collapsed into a single sink before computing responsibility. These states never influence the outcome of the game, so they are no longer treated as players.")
+                    .num_args(0)
+            )
+            .arg(
+                Arg::new("exclude-dummies")
+                    .long("exclude-dummies")
+                    .conflicts_with("benchmark")
+                    .action(ArgAction::SetTrue)
+                    .help("If set, state (groups) that can never change the winner of the game are detected before computing responsibility. They are excluded from the computation and reported with responsibility zero. Only supported for pessimistic and forward responsibility.")
+                    .num_args(0)
+            )
+            .arg(
+                Arg::new("owen")
+                    .long("owen")
+                    .conflicts_with_all(["benchmark", "grouped", "randomised"])
+                    .action(ArgAction::SetTrue)
+                    .help("If set, the Owen value is computed: states with the same labels form a union, responsibility is first distributed among the unions and then among the states of each union. The metric is used on both levels. Only supported by the exact engine and for pessimistic responsibility.")
+                    .num_args(0)
+            )
+            .arg(
+                Arg::new("symmetry")
+                    .long("symmetry")
+                    .conflicts_with("benchmark")
+                    .action(ArgAction::SetTrue)
+                    .help("If set, state (groups) that are interchangeable by an automorphism of the game are detected and reported. Their responsibility is only computed once (exact engines) or averaged (stochastic engine). Only supported for pessimistic and forward responsibility.")
+                    .num_args(0)
+            )
+            .arg(
+                Arg::new("fix")
+                    .long("fix")
+                    .conflicts_with_all(["benchmark", "counterexamples"])
+                    .action(ArgAction::SetTrue)
+                    .help("If set, the winning coalitions of minimum cost are reported as suggestions for fixing the model. By default, every state (group) costs 1, so these are the smallest winning coalitions. Only supported for pessimistic and forward responsibility and up to 64 state (groups).")
+                    .num_args(0)
+            )
+            .arg(
+                Arg::new("costs")
+                    .long("costs")
+                    .requires("fix")
+                    .help("A file with the cost of controlling each state (group) for --fix. Each line gives the name of a state (group) as in the responsibility output, e.g. \"(x=1,y=2)\" or the name of a group, a colon and its cost, e.g. \"sensor: 5\". State (groups) that are not listed cost 1.")
+                    .value_hint(ValueHint::FilePath)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("strategy")
+                    .long("strategy")
+                    .value_name("coalition")
+                    .conflicts_with_all(["benchmark", "counterexamples", "good-label", "ltl", "within"])
+                    .help("Prints a winning strategy for the given coalition, i.e. the successor that each of its states should choose to avoid the bad states. If the coalition loses, prints how the other states force a visit to a bad state instead. The coalition is given by the names of its state (groups) as in the responsibility output, separated by semicolons, e.g. \"(x=1,y=2); (x=2,y=2)\". Only supported for pessimistic and forward responsibility.")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
             .arg(
                 Arg::new("randomised")
                     .long("randomised")
@@ -179,11 +384,48 @@ impl Settings {
                     .help("Use a randomised sampler instead of the exact engine. Argument must either be the number of samples or the sampling duration in seconds (i.e. '-r 10000' or '-r 60s').")
                     .num_args(1)
             )
+            .arg(
+                Arg::new("check")
+                    .long("check")
+                    .value_name("runs")
+                    .requires("randomised")
+                    .conflicts_with_all(["benchmark", "counterexamples", "fix", "strategy", "shorten", "owen"])
+                    .help("Checks the randomised sampler against the exact values, which are computed by the symbolic engine for reachability properties and by the exact engine otherwise. The sampler is run the given number of times (at least two) with consecutive seeds. Reports the error of the mean estimate of every state (group), whether the exact value lies in the 95% confidence interval over the runs, and whether the Shapley value is efficient and gives dummy state (groups) responsibility zero.")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("engine")
+                    .long("engine")
+                    .conflicts_with("benchmark")
+                    .conflicts_with("randomised")
+                    .help("The exact engine that is used. \"exact\" checks every coalition, \"antichain\" computes the responsibility directly from the minimal winning coalitions, which is much faster if there are only few of them. \"symbolic\" represents the winning coalitions as a BDD and also supports more than 64 state (groups).")
+                    .default_value("exact")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("estimator")
+                    .long("estimator")
+                    .conflicts_with("benchmark")
+                    .help("The estimator used by the randomised sampler. \"coalition\" samples a coalition size and then a coalition of that size, \"permutation\" walks random orders of the states (groups) until the coalition wins. The permutation estimator is usually more efficient if coalitions start winning early.")
+                    .default_value("coalition")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
+            .arg(
+                Arg::new("seed")
+                    .long("seed")
+                    .conflicts_with("benchmark")
+                    .help("The seed for the randomised sampler and the generator. For a fixed number of samples, the same seed gives the same result independently of the number of threads. If not set, a random seed is chosen.")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+            )
             .arg(
                 Arg::new("responsibility-version")
                     .long("responsibility-version")
                     .short('v')
-                    .help("Determines whether optimistic or pessimistic responsibility is computed. \"-v o\" selects optimistic responsibility,  \"-v p\" selects pessimistic responsibility. By default, pessimistic responsibility is computed. ")
+                    .help("Determines whether optimistic, pessimistic or forward responsibility is computed. \"-v o\" selects optimistic responsibility,  \"-v p\" selects pessimistic responsibility, \"-v f\" selects forward responsibility, which measures how much each state contributes to a bad state being reachable at all and needs no counterexample. By default, pessimistic responsibility is computed. ")
                     .default_value("p")
                     .num_args(1)
             )
@@ -194,7 +436,6 @@ impl Settings {
                     .help("The state file describing the transition system. If enabled, PRISM is not called and the transition system is instead read directly.")
                     .requires("transition-file")
                     .requires("label-file")
-                    .requires("counterexample")
                     .conflicts_with("model-input-file")
                     .conflicts_with("benchmark")
                     .value_name("state_file.sta")
@@ -208,7 +449,6 @@ impl Settings {
                     .help("The transition file describing the transition system. If enabled, PRISM is not called and the transition system is instead read directly.")
                     .requires("state-file")
                     .requires("label-file")
-                    .requires("counterexample")
                     .conflicts_with("model-input-file")
                     .conflicts_with("benchmark")
                     .value_name("transition_file.tra")
@@ -222,7 +462,6 @@ impl Settings {
                     .help("The label file describing the transition system. If enabled, PRISM is not called and the transition system is instead read directly.")
                     .requires("state-file")
                     .requires("transition-file")
-                    .requires("counterexample")
                     .conflicts_with("model-input-file")
                     .conflicts_with("benchmark")
                     .value_name("label_file.lab")
@@ -233,13 +472,29 @@ impl Settings {
                 Arg::new("counterexample")
                     .long("counterexample")
                     .short('c')
-                    .help("A file that contains a counterexample. The file must have the same format as PRISM's counterexamples with one state per line. If there are three variables with values 1, 3 and 12, then a state has form (1,3,12).")
+                    .help("A file that contains a counterexample. The file must have the same format as PRISM's counterexamples with one state per line. If there are three variables with values 1, 3 and 12, then a state has form (1,3,12). If given several times, responsibility is aggregated over all counterexamples.")
                     .value_name("counterexample.ce")
                     .value_hint(ValueHint::FilePath)
                     .num_args(1)
-                    .required_unless_present_any(&["model-input-file", "benchmark"])
+                    .action(ArgAction::Append)
                     .conflicts_with("benchmark")
             )
+            .arg(
+                Arg::new("counterexamples")
+                    .long("counterexamples")
+                    .help("Aggregates responsibility over several counterexamples instead of using a single one. \"shortest\" uses all shortest paths to a bad state, \"random:<k>\" draws k random paths to a bad state (using --seed). Reports the mean, minimum and maximum responsibility of every state (group).")
+                    .value_hint(ValueHint::Other)
+                    .num_args(1)
+                    .conflicts_with_all(["benchmark", "counterexample"])
+            )
+            .arg(
+                Arg::new("shorten")
+                    .long("shorten")
+                    .conflicts_with_all(["benchmark", "counterexamples", "good-label", "ltl"])
+                    .action(ArgAction::SetTrue)
+                    .help("If set, the counterexample is shortened to a shortest path to the first bad state that it visits, which removes loops and detours, and responsibility is also computed for the shortened counterexample. Reports how the responsibility of every state (group) differs between both counterexamples.")
+                    .num_args(0)
+            )
             .arg(
                 Arg::new("thread-count")
                     .long("thread-count")
@@ -292,10 +547,48 @@ impl Settings {
                 .map(|p| p.to_string()),
         };
 
+        let responsibility_version = match matches
+            .get_one::<String>("responsibility-version")
+            .unwrap()
+            .as_str()
+        {
+            "o" => ResponsibilityVersion::Optimistic,
+            "p" => ResponsibilityVersion::Pessimistic,
+            "f" => ResponsibilityVersion::Forward,
+            version => panic!("Unknown responsibility version {}", version),
+        };
+
         let subcommand = if let Some(benchmark_file) = matches.get_one::<String>("benchmark") {
             Subcommand::Benchmark(BenchmarkSubcommand {
                 file: benchmark_file.clone(),
             })
+        } else if let Some(stem) = matches.get_one::<String>("generate") {
+            let parse_count = |name: &str, default: usize| {
+                matches.get_one::<String>(name).map_or(default, |s| {
+                    s.parse::<usize>()
+                        .unwrap_or_else(|e| panic!("Could not parse --{}: {}", name, e))
+                })
+            };
+            let settings = GeneratorSettings {
+                state_count: parse_count("states", 100),
+                branching: parse_count("branching", 2),
+                bad_density: matches.get_one::<String>("bad-density").map_or(0.05, |s| {
+                    s.parse::<f64>().expect("Could not parse --bad-density.")
+                }),
+                counterexample_length: parse_count("ce-length", 5),
+                label_count: parse_count("labels", 0),
+                seed: matches.get_one::<String>("seed").map_or_else(
+                    || fastrand::u64(..),
+                    |s| s.parse::<u64>().expect("Could not parse seed."),
+                ),
+            };
+            Subcommand::Generate(GenerateSubcommand {
+                stem: stem.clone(),
+                bad_label: matches
+                    .get_one::<String>("bad-label")
+                    .map_or("bad".to_string(), |s| s.to_string()),
+                settings,
+            })
         } else {
             let model_input = if let Some(state_file) = matches.get_one::<String>("state-file") {
                 let transition_file = matches
@@ -315,14 +608,59 @@ impl Settings {
                 }
             };
 
-            let counterexample_input = matches.get_one::<String>("counterexample").map_or(
-                CounterexampleInput::ModelChecker,
-                |c| CounterexampleInput::File {
-                    file: c.to_string(),
+            let counterexample_files = matches
+                .get_many::<String>("counterexample")
+                .map_or(Vec::new(), |c| c.cloned().collect());
+            let counterexample_input = match matches.get_one::<String>("counterexamples") {
+                Some(_) if responsibility_version == ResponsibilityVersion::Forward => {
+                    panic!("Forward responsibility does not depend on a counterexample, remove --counterexamples.")
+                }
+                Some(counterexamples) => match counterexamples.split_once(':') {
+                    None if counterexamples == "shortest" => CounterexampleInput::AllShortest,
+                    Some(("random", count)) => CounterexampleInput::Random {
+                        count: count
+                            .parse()
+                            .expect("Could not parse number of random counterexamples."),
+                    },
+                    _ => panic!("Unknown counterexamples {}", counterexamples),
                 },
-            );
+                None if responsibility_version == ResponsibilityVersion::Forward => {
+                    if !counterexample_files.is_empty() {
+                        panic!("Forward responsibility does not depend on a counterexample, remove --counterexample.")
+                    }
+                    CounterexampleInput::Unused
+                }
+                None => match counterexample_files.len() {
+                    0 => CounterexampleInput::ModelChecker,
+                    1 => CounterexampleInput::File {
+                        file: counterexample_files[0].clone(),
+                    },
+                    _ => CounterexampleInput::Files {
+                        files: counterexample_files,
+                    },
+                },
+            };
 
             let grouped = matches.get_flag("grouped");
+            let prune = matches.get_flag("prune");
+            let exclude_dummies = matches.get_flag("exclude-dummies");
+            let symmetry = matches.get_flag("symmetry");
+            let owen = matches.get_flag("owen");
+            let groups_file = matches.get_one::<String>("groups").map(|g| g.to_string());
+            let group_variables = matches
+                .get_many::<String>("group-by-var")
+                .map_or(Vec::new(), |v| v.cloned().collect());
+            let strategy_coalition = matches.get_one::<String>("strategy").map(|coalition| {
+                coalition
+                    .split(';')
+                    .map(|name| name.trim().to_string())
+                    .filter(|name| !name.is_empty())
+                    .collect()
+            });
+            let fix = matches.get_flag("fix");
+            let costs = matches
+                .get_one::<String>("costs")
+                .map_or(Vec::new(), |file| parse_costs(file));
 
             let responsibility_output = matches.get_one::<String>("responsibility-file").map_or(
                 ResponsibilityOutput::Stdout,
@@ -340,6 +678,25 @@ impl Settings {
             let bad_label = matches
                 .get_one::<String>("bad-label")
                 .map(|s| s.to_string());
+            let good_label = matches
+                .get_one::<String>("good-label")
+                .map(|s| s.to_string());
+            let infinitely_often = matches.get_flag("infinitely-often");
+            let ltl = matches.get_one::<String>("ltl").map(|s| s.to_string());
+            let within = matches.get_one::<String>("within").map(|s| {
+                s.parse::<usize>()
+                    .expect("Could not parse number of steps.")
+            });
+            let shorten = matches.get_flag("shorten");
+            let check_runs = matches.get_one::<String>("check").map(|s| {
+                let runs = s
+                    .parse::<usize>()
+                    .expect("Could not parse number of runs for --check.");
+                if runs < 2 {
+                    panic!("--check requires at least two runs.");
+                }
+                runs
+            });
 
             let engine = match matches.get_one::<String>("randomised") {
                 Some(value) => {
@@ -356,41 +713,56 @@ impl Settings {
                                 .expect("Could not parse randomised target sample count."),
                         )
                     };
-                    Engine::Stochastic(target)
+                    let estimator = match matches.get_one::<String>("estimator").unwrap().as_str() {
+                        "coalition" => Estimator::Coalition,
+                        "permutation" => Estimator::Permutation,
+                        estimator => panic!("Unknown estimator {}", estimator),
+                    };
+                    Engine::Stochastic(target, estimator)
                 }
-                None => Engine::Exact,
+                None => match matches.get_one::<String>("engine").unwrap().as_str() {
+                    "exact" => Engine::Exact,
+                    "antichain" => Engine::Antichain,
+                    "symbolic" => Engine::Symbolic,
+                    engine => panic!("Unknown engine {}", engine),
+                },
             };
 
+            let seed = matches
+                .get_one::<String>("seed")
+                .map(|s| s.parse::<u64>().expect("Could not parse seed."));
+
             Subcommand::Run(RunSubcommand {
                 model_input,
                 counterexample_input,
                 bad_label,
+                good_label,
+                infinitely_often,
+                ltl,
+                within,
+                shorten,
+                check_runs,
                 responsibility_output,
                 engine,
                 grouped,
+                seed,
+                prune,
+                exclude_dummies,
+                symmetry,
+                owen,
+                groups_file,
+                group_variables,
+                fix,
+                costs,
+                strategy_coalition,
             })
         };
 
-        let responsibility_metric = match matches.get_one::<String>("metric").unwrap().as_str() {
-            "shapley" => WeightType::Shapley,
-            "banzhaf" => WeightType::Banzhaf,
-            "count" => WeightType::Count,
-            metric => panic!("Unknown metric {}", metric),
-        };
+        let responsibility_metric = parse_metric(matches.get_one::<String>("metric").unwrap());
         let thread_count = matches
             .get_one::<String>("thread-count")
             .map(|t| t.parse::<usize>().expect("Could not parse thread-count."));
 
-        let responsibility_version = match matches
-            .get_one::<String>("responsibility-version")
-            .unwrap()
-            .as_str()
-        {
-            "o" => ResponsibilityVersion::Optimistic,
-            "p" => ResponsibilityVersion::Pessimistic,
-            version => panic!("Unknown responsibility version {}", version),
-        };
-
         let no_prism = matches.get_flag("no-prism");
 
         Settings {
@@ -403,3 +775,104 @@ impl Settings {
         }
     }
 }
+
+fn parse_metric(metric: &str) -> WeightType {
+    match metric.split_once(':') {
+        None => match metric {
+            "shapley" => WeightType::Shapley,
+            "banzhaf" => WeightType::Banzhaf,
+            "count" => WeightType::Count,
+            "deegan-packel" => WeightType::DeeganPackel,
+            "holler-packel" => WeightType::HollerPackel,
+            "johnston" => WeightType::Johnston,
+            metric => panic!("Unknown metric {}", metric),
+        },
+        Some(("beta", parameters)) => {
+            let (alpha, beta) = parameters
+                .split_once(',')
+                .expect("Beta metric must be specified as beta:<alpha>,<beta>.");
+            let alpha = parse_rational(alpha);
+            let beta = parse_rational(beta);
+            if !alpha.is_positive() || !beta.is_positive() {
+                panic!("The parameters of the beta metric must be positive.");
+            }
+            WeightType::Beta(alpha, beta)
+        }
+        Some(("weighted-banzhaf", p)) => {
+            let p = parse_rational(p);
+            if p.is_negative() || p > BigRational::one() {
+                panic!("The parameter of the weighted Banzhaf metric must be between 0 and 1.");
+            }
+            WeightType::WeightedBanzhaf(p)
+        }
+        Some(("weights", file)) => {
+            let content = std::fs::read_to_string(file)
+                .unwrap_or_else(|e| panic!("Unable to open weight file \"{}\": {}", file, e));
+            let weights = content
+                .lines()
+                .map(|l| l.trim())
+                .filter(|l| !l.is_empty() && !l.starts_with("//"))
+                .map(parse_rational)
+                .collect::<Vec<_>>();
+            if weights.iter().any(|w| w.is_negative()) {
+                panic!("Weights in \"{}\" must not be negative.", file);
+            }
+            WeightType::Custom(weights)
+        }
+        Some((metric, _)) => panic!("Unknown metric {}", metric),
+    }
+}
+
+// Parses a cost file, which gives a state (group) and its cost separated by a colon on each line
+fn parse_costs(file: &str) -> Vec<(String, BigRational)> {
+    let content = std::fs::read_to_string(file)
+        .unwrap_or_else(|e| panic!("Unable to open cost file \"{}\": {}", file, e));
+    content
+        .lines()
+        .map(|l| l.trim())
+        .filter(|l| !l.is_empty() && !l.starts_with("//"))
+        .map(|line| {
+            let (name, cost) = line.rsplit_once(':').unwrap_or_else(|| {
+                panic!(
+                    "Cost definition \"{}\" has incorrect format, as it is missing \":\".",
+                    line
+                )
+            });
+            let cost = parse_rational(cost);
+            if cost.is_negative() {
+                panic!("Costs in \"{}\" must not be negative.", file);
+            }
+            (name.trim().to_string(), cost)
+        })
+        .collect()
+}
+
+// Parses integers, fractions (e.g. 1/3) and decimals (e.g. 0.25) into exact rationals
+fn parse_rational(value: &str) -> BigRational {
+    let value = value.trim();
+    let parse_int = |s: &str| {
+        s.parse::<BigInt>()
+            .unwrap_or_else(|_| panic!("Could not parse number \"{}\".", value))
+    };
+    if let Some((numerator, denominator)) = value.split_once('/') {
+        let denominator = parse_int(denominator);
+        if denominator.is_zero() {
+            panic!("Could not parse number \"{}\": denominator is zero.", value);
+        }
+        BigRational::new(parse_int(numerator), denominator)
+    } else if let Some((integer, fraction)) = value.split_once('.') {
+        let scale = BigInt::from(10).pow(fraction.len() as u32);
+        let fraction = BigRational::new(parse_int(fraction), scale);
+        let integer = match integer {
+            "" | "-" => BigRational::zero(),
+            integer => BigRational::from_integer(parse_int(integer)),
+        };
+        if value.starts_with('-') {
+            integer - fraction
+        } else {
+            integer + fraction
+        }
+    } else {
+        BigRational::from_integer(parse_int(value))
+    }
+}
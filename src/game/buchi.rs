@@ -0,0 +1,143 @@
+use crate::game::{Game, Player};
+
+// Solves the Büchi game in which the safety player wins if the play visits a good state infinitely
+// often or ends in a good state without successors. The reachability player (and the path player)
+// win otherwise, in particular if the play ends in any other state without successors. As for
+// reachability, the path player may only take transitions of the counterexample.
+//
+// The winning region is computed by the classic nested fixpoint: the states from which the safety
+// player cannot even reach a good state (while avoiding states that are already lost) form a trap,
+// and everything the reachability player can attract to a trap is lost. This is repeated until no
+// new trap is found.
+pub struct BuchiSolver<'a> {
+    game: &'a Game,
+    good_states: &'a [usize],
+    path_successor_count: Vec<usize>,
+}
+
+impl<'a> BuchiSolver<'a> {
+    pub fn new(game: &'a Game, good_states: &'a [usize]) -> Self {
+        let mut path_successor_count = vec![0; game.states.len()];
+        for predecessors in &game.state_predecessors {
+            for transition in &predecessors.predecessors {
+                if transition.on_path {
+                    path_successor_count[transition.source] += 1;
+                }
+            }
+        }
+        Self {
+            game,
+            good_states,
+            path_successor_count,
+        }
+    }
+
+    // Returns, for every state, whether the reachability player wins from it under the current
+    // owners
+    pub fn reach_winning_region(&self) -> Vec<bool> {
+        let state_count = self.game.states.len();
+        // The states from which the safety player loses
+        let mut lost = vec![false; state_count];
+        loop {
+            let mut recurrent = vec![false; state_count];
+            let mut open_set = Vec::new();
+            for &good_state in self.good_states {
+                if !lost[good_state] && !recurrent[good_state] {
+                    recurrent[good_state] = true;
+                    open_set.push(good_state);
+                }
+            }
+            self.attract(false, &mut recurrent, &lost, open_set);
+
+            let mut found_trap = false;
+            for state in 0..state_count {
+                if !recurrent[state] && !lost[state] {
+                    lost[state] = true;
+                    found_trap = true;
+                }
+            }
+            if !found_trap {
+                break;
+            }
+            // All lost states are opened, so that the counters include the states lost earlier
+            let open_set = (0..state_count).filter(|&state| lost[state]).collect();
+            let nothing_blocked = vec![false; state_count];
+            self.attract(true, &mut lost, &nothing_blocked, open_set);
+        }
+        lost
+    }
+
+    // Adds all states from which the given player can force a visit to an attracted state without
+    // passing through a blocked state. The open set contains the attracted states whose
+    // predecessors have not been examined yet. The path player is on the side of the reachability
+    // player.
+    fn attract(
+        &self,
+        for_reach: bool,
+        attracted: &mut [bool],
+        blocked: &[bool],
+        mut open_set: Vec<usize>,
+    ) {
+        // States without successors have nothing to count down and are never attracted
+        let mut remaining = self
+            .game
+            .states
+            .iter()
+            .enumerate()
+            .map(|(index, state)| match (state.owner, for_reach) {
+                (Player::Safe, false) | (Player::Reach | Player::Path, true) => 1,
+                (Player::Path, false) => self.path_successor_count[index],
+                (Player::Safe | Player::Reach, _) => state.successor_count,
+            })
+            .collect::<Vec<_>>();
+
+        while let Some(open_index) = open_set.pop() {
+            for transition in &self.game.state_predecessors[open_index].predecessors {
+                let source = transition.source;
+                if attracted[source] || blocked[source] {
+                    continue;
+                }
+                if self.game.states[source].owner == Player::Path && !transition.on_path {
+                    continue;
+                }
+                remaining[source] -= 1;
+                if remaining[source] == 0 {
+                    attracted[source] = true;
+                    open_set.push(source);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn winning_regions() {
+        // 1 is a good state with a self-loop and 2 a bad one. 4 is good but can only be visited
+        // once. 5 and 6 have no successors, of which only 5 is good. 3 belongs to the coalition and
+        // 7 is a path state that may only move to 1.
+        let successors = vec![
+            vec![1, 2],
+            vec![1],
+            vec![2],
+            vec![1, 2],
+            vec![2],
+            vec![],
+            vec![],
+            vec![1, 2],
+        ];
+        let good_states = vec![1, 4, 5];
+        let mut game = Game::from_successors(0, &successors, Vec::new());
+        game.add_to_coalition(3);
+        game.mark_path_transitions(&[7], &[(7, 1)]);
+
+        let reach_wins = BuchiSolver::new(&game, &good_states).reach_winning_region();
+        assert_eq!(
+            reach_wins,
+            vec![true, false, true, false, true, false, true, false]
+        );
+    }
+}
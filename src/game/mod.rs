@@ -1,27 +1,29 @@
+mod buchi;
 mod super_attractor;
+mod symbolic_attractor;
 
+use crate::bdd::{Bdd, BddNode};
 use crate::transition_systems::TransitionSystem;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct Game {
     pub initial_state: usize,
     pub states: Vec<State>,
     pub state_predecessors: Vec<StatePredecessors>,
     pub bad_states: Vec<usize>,
     pub labels: Vec<Label>,
+    pub objective: Objective,
 }
 
-impl Default for Game {
-    fn default() -> Self {
-        Self {
-            initial_state: 0,
-            states: Vec::new(),
-            state_predecessors: Vec::new(),
-            bad_states: Vec::new(),
-            labels: Vec::new(),
-        }
-    }
+// What the reachability player has to achieve to win. For liveness properties, the reachability
+// player is the one that violates the property, i.e. the one that avoids the good states.
+#[derive(Clone, Default)]
+pub enum Objective {
+    #[default]
+    ReachBad,
+    // The reachability player wins if the play visits the given (good) states only finitely often
+    AvoidRecurrence(Vec<usize>),
 }
 
 impl Game {
@@ -50,7 +52,7 @@ impl Game {
             }
         }
 
-        if unlabelled_states.states.len() > 0 {
+        if !unlabelled_states.states.is_empty() {
             game.labels.push(unlabelled_states);
         }
 
@@ -65,6 +67,30 @@ impl Game {
         game
     }
 
+    // Builds a game on an arbitrary graph, e.g. the product of a transition system with an
+    // automaton. All states are owned by the reachability player and there are no bad states.
+    pub fn from_successors(
+        initial_state: usize,
+        successors: &[Vec<usize>],
+        labels: Vec<(String, Vec<usize>)>,
+    ) -> Self {
+        let mut game = Game {
+            initial_state,
+            ..Game::default()
+        };
+        for _ in successors {
+            game.states.push(State::new(Player::Reach));
+            game.state_predecessors.push(StatePredecessors::default());
+        }
+        for (source, targets) in successors.iter().enumerate() {
+            for &target in targets {
+                game.add_transition(source, target);
+            }
+        }
+        game.replace_labels(labels);
+        game
+    }
+
     fn add_transition(&mut self, from: usize, to: usize) {
         self.states[from].successor_count += 1;
         self.state_predecessors[to]
@@ -95,6 +121,230 @@ impl Game {
         }
     }
 
+    // Changes the objective to a liveness property over the given good states. If
+    // `infinitely_often` is set, the good states must be visited infinitely often, otherwise they
+    // must be visited at least once. The latter is reduced to the former by replacing all
+    // transitions of good states by self-loops.
+    pub fn set_liveness_objective(&mut self, good_states: Vec<usize>, infinitely_often: bool) {
+        if !infinitely_often {
+            for predecessors in &mut self.state_predecessors {
+                predecessors
+                    .predecessors
+                    .retain(|transition| !good_states.contains(&transition.source));
+            }
+            for &good_state in &good_states {
+                self.states[good_state].successor_count = 1;
+                self.state_predecessors[good_state]
+                    .predecessors
+                    .push(Transition::new(good_state));
+            }
+        }
+        self.objective = Objective::AvoidRecurrence(good_states);
+    }
+
+    // Returns the states that the reachability player has to reach (for a reachability objective)
+    // or to avoid (for a liveness objective)
+    pub fn objective_states(&self) -> &[usize] {
+        match &self.objective {
+            Objective::ReachBad => &self.bad_states,
+            Objective::AvoidRecurrence(good_states) => good_states,
+        }
+    }
+
+    // Marks the given states as path states that may only take the given transitions. This is used
+    // instead of `mark_counterexample_path` if a state of the counterexample corresponds to several
+    // states of the game, e.g. in a product with an automaton.
+    pub fn mark_path_transitions(&mut self, states: &[usize], transitions: &[(usize, usize)]) {
+        for &state in states {
+            self.states[state].owner = Player::Path;
+            self.states[state].default_owner = Player::Path;
+        }
+        for &(from, to) in transitions {
+            for to_transition in &mut self.state_predecessors[to].predecessors {
+                if to_transition.source == from {
+                    to_transition.on_path = true;
+                }
+            }
+        }
+    }
+
+    // Replaces the labels of the model by user-defined groups of states, which are then used
+    // instead of the labels for grouping
+    pub fn replace_labels(&mut self, groups: Vec<(String, Vec<usize>)>) {
+        self.labels = groups
+            .into_iter()
+            .map(|(name, states)| Label { name, states })
+            .collect();
+    }
+
+    // Unrolls the game for the given number of steps, so that the reachability player only wins by
+    // reaching a bad state within these steps. Every state of the unrolled game is a copy of a
+    // state at the step at which it is reached, and copies at the last step have no successors.
+    // Owners, path transitions and labels carry over to the copies. Returns the unrolled game and,
+    // for every state, its copies.
+    pub fn unroll(&self, steps: usize) -> (Game, Vec<Vec<usize>>) {
+        let mut successors = vec![Vec::new(); self.states.len()];
+        for (target, predecessors) in self.state_predecessors.iter().enumerate() {
+            for transition in &predecessors.predecessors {
+                successors[transition.source].push((target, transition.on_path));
+            }
+        }
+
+        let mut unrolled = Game::default();
+        let mut copies = vec![Vec::new(); self.states.len()];
+        // The states at the current step and their copies
+        let mut layer = vec![(self.initial_state, 0)];
+        unrolled
+            .states
+            .push(State::new(self.states[self.initial_state].default_owner));
+        unrolled
+            .state_predecessors
+            .push(StatePredecessors::default());
+        for step in 0..=steps {
+            let mut next_layer = Vec::new();
+            let mut next_copies = HashMap::new();
+            for &(state, copy) in &layer {
+                copies[state].push(copy);
+                if self.bad_states.contains(&state) {
+                    unrolled.bad_states.push(copy);
+                    continue;
+                }
+                if step == steps {
+                    continue;
+                }
+                for &(target, on_path) in &successors[state] {
+                    let target_copy = *next_copies.entry(target).or_insert_with(|| {
+                        unrolled
+                            .states
+                            .push(State::new(self.states[target].default_owner));
+                        unrolled
+                            .state_predecessors
+                            .push(StatePredecessors::default());
+                        next_layer.push((target, unrolled.states.len() - 1));
+                        unrolled.states.len() - 1
+                    });
+                    unrolled.add_transition(copy, target_copy);
+                    let predecessors = &mut unrolled.state_predecessors[target_copy].predecessors;
+                    predecessors.last_mut().unwrap().on_path = on_path;
+                }
+            }
+            layer = next_layer;
+        }
+
+        unrolled.labels = self
+            .labels
+            .iter()
+            .map(|label| Label {
+                name: label.name.clone(),
+                states: label
+                    .states
+                    .iter()
+                    .flat_map(|&state| copies[state].iter().copied())
+                    .collect(),
+            })
+            .collect();
+        (unrolled, copies)
+    }
+
+    // Removes all transitions of states that are unreachable from the initial state and redirects
+    // all transitions into states that cannot reach a bad state to a single new sink state. Neither
+    // kind of state can influence the winner, and as they are left without successors, they are
+    // no longer significant. For liveness objectives, the same applies to states that cannot reach
+    // a good state, as the reachability player wins from them just like from the sink. State
+    // indices stay valid, so the states can still be matched with the transition system.
+    pub fn prune(&mut self) -> PruneSummary {
+        let reachable = self
+            .breadth_first_positions()
+            .iter()
+            .map(Option::is_some)
+            .collect::<Vec<_>>();
+
+        let mut can_reach_bad = vec![false; self.states.len()];
+        let mut open_set = self.objective_states().to_vec();
+        for &target_state in self.objective_states() {
+            can_reach_bad[target_state] = true;
+        }
+        while let Some(state) = open_set.pop() {
+            for transition in &self.state_predecessors[state].predecessors {
+                if !can_reach_bad[transition.source] {
+                    can_reach_bad[transition.source] = true;
+                    open_set.push(transition.source);
+                }
+            }
+        }
+
+        let is_relevant = |state: usize| reachable[state] && can_reach_bad[state];
+        let sink = self.states.len();
+        let mut sink_predecessors = StatePredecessors::default();
+        let mut has_sink_transition = vec![false; self.states.len()];
+        for (state, predecessors) in self.state_predecessors.iter_mut().enumerate() {
+            let old_predecessors = std::mem::take(&mut predecessors.predecessors);
+            for transition in old_predecessors {
+                if !is_relevant(transition.source) {
+                    continue;
+                }
+                if is_relevant(state) {
+                    predecessors.predecessors.push(transition);
+                } else if !has_sink_transition[transition.source] {
+                    has_sink_transition[transition.source] = true;
+                    sink_predecessors.predecessors.push(transition);
+                }
+            }
+        }
+
+        for state in &mut self.states {
+            state.successor_count = 0;
+        }
+        if !sink_predecessors.predecessors.is_empty() {
+            self.states.push(State::new(Player::Reach));
+            self.state_predecessors.push(sink_predecessors);
+        }
+        for predecessors in &self.state_predecessors {
+            for transition in &predecessors.predecessors {
+                self.states[transition.source].successor_count += 1;
+            }
+        }
+
+        self.bad_states.retain(|&state| reachable[state]);
+        if let Objective::AvoidRecurrence(good_states) = &mut self.objective {
+            good_states.retain(|&state| reachable[state]);
+        }
+        for label in &mut self.labels {
+            label.states.retain(|&state| is_relevant(state));
+        }
+
+        let unreachable_states = reachable.iter().filter(|&&r| !r).count();
+        PruneSummary {
+            unreachable_states,
+            collapsed_states: (0..sink)
+                .filter(|&s| reachable[s] && !can_reach_bad[s])
+                .count(),
+        }
+    }
+
+    // Returns all transitions as (source, target, on_path)
+    pub fn transitions(&self) -> Vec<(usize, usize, bool)> {
+        let mut transitions = Vec::new();
+        for (target, predecessors) in self.state_predecessors.iter().enumerate() {
+            for transition in &predecessors.predecessors {
+                transitions.push((transition.source, target, transition.on_path));
+            }
+        }
+        transitions
+    }
+
+    // Returns, for every state, the successors that the play can continue with, where path states
+    // may only take the transitions of the counterexample
+    fn playable_successors(&self) -> Vec<Vec<usize>> {
+        let mut successors = vec![Vec::new(); self.states.len()];
+        for (source, target, on_path) in self.transitions() {
+            if self.states[source].owner != Player::Path || on_path {
+                successors[source].push(target);
+            }
+        }
+        successors
+    }
+
     pub fn get_significant_states(&self) -> Vec<usize> {
         let mut res = Vec::new();
         for (state_index, state) in self.states.iter().enumerate() {
@@ -141,6 +391,172 @@ impl Game {
     }
 
     pub fn determine_winner(&mut self) -> Player {
+        if let Some(solver) = self.buchi_solver() {
+            return if solver.reach_winning_region()[self.initial_state] {
+                Player::Reach
+            } else {
+                Player::Safe
+            };
+        }
+        let initial_state = self.initial_state;
+        if self.run_attractor(|state| state == initial_state) {
+            Player::Reach
+        } else {
+            Player::Safe
+        }
+    }
+
+    // Returns, for every state, whether the reachability player can force a visit to a bad state
+    // from it under the current owners. For liveness objectives, this is the winning region of the
+    // reachability player.
+    pub fn compute_attractor(&mut self) -> Vec<bool> {
+        if let Some(solver) = self.buchi_solver() {
+            return solver.reach_winning_region();
+        }
+        let mut attracted = vec![false; self.states.len()];
+        for &bad_state in &self.bad_states {
+            attracted[bad_state] = true;
+        }
+        self.run_attractor(|state| {
+            attracted[state] = true;
+            false
+        });
+        attracted
+    }
+
+    // Returns a positional winning strategy of the safety player under the current owners, i.e. the
+    // successor that each coalition state chooses, or None if the reachability player wins. Any
+    // successor outside the attractor of the bad states is winning. Only coalition states that can
+    // be visited while the strategy is followed are included.
+    pub fn safe_strategy(&mut self) -> Option<Vec<(usize, usize)>> {
+        if !matches!(self.objective, Objective::ReachBad) {
+            panic!("Strategies are only supported for reachability objectives.");
+        }
+        let attracted = self.compute_attractor();
+        if attracted[self.initial_state] {
+            return None;
+        }
+
+        let successors = self.playable_successors();
+        let mut strategy = Vec::new();
+        let mut visited = vec![false; self.states.len()];
+        visited[self.initial_state] = true;
+        let mut queue = VecDeque::from([self.initial_state]);
+        while let Some(state) = queue.pop_front() {
+            let mut next_states = successors[state].clone();
+            if self.states[state].owner == Player::Safe {
+                // A coalition state outside the attractor has a successor outside of it, unless it
+                // has no successors at all
+                next_states = next_states
+                    .into_iter()
+                    .find(|&next_state| !attracted[next_state])
+                    .into_iter()
+                    .collect();
+                if let Some(&choice) = next_states.first() {
+                    strategy.push((state, choice));
+                }
+            }
+            for next_state in next_states {
+                if !visited[next_state] {
+                    visited[next_state] = true;
+                    queue.push_back(next_state);
+                }
+            }
+        }
+        strategy.sort();
+        Some(strategy)
+    }
+
+    // Returns, for every state in the attractor of the bad states, its rank, i.e. a number of steps
+    // within which the reachability player can force a visit to a bad state from it. Bad states
+    // have rank 0. The ranks are computed in the order in which the attractor adds the states, so
+    // the successors that caused a state to be added already have a rank.
+    pub fn compute_attractor_ranks(&mut self) -> Vec<Option<usize>> {
+        if !matches!(self.objective, Objective::ReachBad) {
+            panic!("Attractor ranks are only supported for reachability objectives.");
+        }
+        let mut order = Vec::new();
+        self.run_attractor(|state| {
+            order.push(state);
+            false
+        });
+
+        let successors = self.playable_successors();
+        let mut ranks = vec![None; self.states.len()];
+        for &bad_state in &self.bad_states {
+            ranks[bad_state] = Some(0);
+        }
+        for state in order {
+            let successor_ranks = successors[state].iter().filter_map(|&next| ranks[next]);
+            // The safety player delays the visit as long as possible, the others hurry
+            let rank = if self.states[state].owner == Player::Safe {
+                successor_ranks.max()
+            } else {
+                successor_ranks.min()
+            };
+            ranks[state] = rank.map(|rank| rank + 1);
+        }
+        ranks
+    }
+
+    // Returns a witness that the reachability player wins under the current owners, or None if the
+    // safety player wins. The witness is the part of the game that can be visited while the
+    // reachability and path player follow the attractor strategy, i.e. always move to a successor
+    // of the smallest rank, so that every play in it reaches a bad state. The states are listed in
+    // breadth-first order from the initial state.
+    pub fn reach_witness(&mut self) -> Option<Vec<WitnessState>> {
+        let ranks = self.compute_attractor_ranks();
+        ranks[self.initial_state]?;
+
+        let successors = self.playable_successors();
+        let mut witness = Vec::new();
+        let mut visited = vec![false; self.states.len()];
+        visited[self.initial_state] = true;
+        let mut queue = VecDeque::from([self.initial_state]);
+        while let Some(state) = queue.pop_front() {
+            let owner = self.states[state].owner;
+            let rank = ranks[state].unwrap();
+            let next_states = if rank == 0 {
+                Vec::new()
+            } else if owner == Player::Safe {
+                successors[state].clone()
+            } else {
+                successors[state]
+                    .iter()
+                    .copied()
+                    .filter(|&next_state| ranks[next_state].is_some())
+                    .min_by_key(|&next_state| ranks[next_state])
+                    .into_iter()
+                    .collect()
+            };
+            for &next_state in &next_states {
+                if !visited[next_state] {
+                    visited[next_state] = true;
+                    queue.push_back(next_state);
+                }
+            }
+            witness.push(WitnessState {
+                state,
+                owner,
+                rank,
+                successors: next_states,
+            });
+        }
+        Some(witness)
+    }
+
+    fn buchi_solver(&self) -> Option<buchi::BuchiSolver<'_>> {
+        match &self.objective {
+            Objective::ReachBad => None,
+            Objective::AvoidRecurrence(good_states) => {
+                Some(buchi::BuchiSolver::new(self, good_states))
+            }
+        }
+    }
+
+    // Computes the attractor of the bad states and calls `on_attracted` for every other state that
+    // is added to it. Stops and returns true as soon as `on_attracted` returns true.
+    fn run_attractor(&mut self, mut on_attracted: impl FnMut(usize) -> bool) -> bool {
         self.reset_attractor_counts();
 
         let mut open_set = Vec::new();
@@ -167,8 +583,8 @@ impl Game {
                         }
                     }
                     if source_state.attractor_count == 0 {
-                        if transition.source == self.initial_state {
-                            return Player::Reach;
+                        if on_attracted(transition.source) {
+                            return true;
                         }
                         open_set.push(transition.source);
                     }
@@ -176,7 +592,7 @@ impl Game {
             }
         }
 
-        Player::Safe
+        false
     }
 
     fn reset_attractor_counts(&mut self) {
@@ -189,6 +605,47 @@ impl Game {
         }
     }
 
+    // Returns the coalitions for which the reachability player wins, given the coalitions in which
+    // each state is controlled by the safety player.
+    pub fn compute_reach_winning_coalitions(
+        &self,
+        bdd: &mut Bdd,
+        coalition_membership: &[BddNode],
+    ) -> BddNode {
+        if !matches!(self.objective, Objective::ReachBad) {
+            panic!("The symbolic engine only supports reachability objectives.")
+        }
+        let attractor = symbolic_attractor::SymbolicAttractor::new(self, coalition_membership);
+        attractor.run(bdd)[self.initial_state]
+    }
+
+    // Returns, for every state, the position at which a breadth-first search from the initial state
+    // discovers it, or None if it is unreachable.
+    pub fn breadth_first_positions(&self) -> Vec<Option<usize>> {
+        let mut successors = vec![Vec::new(); self.states.len()];
+        for (target, predecessors) in self.state_predecessors.iter().enumerate() {
+            for transition in &predecessors.predecessors {
+                successors[transition.source].push(target);
+            }
+        }
+
+        let mut positions = vec![None; self.states.len()];
+        let mut queue = std::collections::VecDeque::new();
+        positions[self.initial_state] = Some(0);
+        queue.push_back(self.initial_state);
+        let mut discovered = 1;
+        while let Some(state) = queue.pop_front() {
+            for &successor in &successors[state] {
+                if positions[successor].is_none() {
+                    positions[successor] = Some(discovered);
+                    discovered += 1;
+                    queue.push_back(successor);
+                }
+            }
+        }
+        positions
+    }
+
     #[allow(dead_code)]
     pub fn find_minimal_causes(&self) -> Vec<Vec<usize>> {
         let mut super_attractor = super_attractor::SuperAttractor::new(self.clone());
@@ -196,6 +653,19 @@ impl Game {
     }
 }
 
+pub struct PruneSummary {
+    pub unreachable_states: usize,
+    pub collapsed_states: usize, // Reachable states that cannot reach a bad state
+}
+
+// A state of the witness that the reachability player wins, see `Game::reach_witness`
+pub struct WitnessState {
+    pub state: usize,
+    pub owner: Player,
+    pub rank: usize,
+    pub successors: Vec<usize>, // The successors that the play may continue with
+}
+
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 pub enum Player {
     Reach,
@@ -224,19 +694,11 @@ impl State {
     }
 }
 
-#[derive(Clone)]
+#[derive(Clone, Default)]
 pub struct StatePredecessors {
     pub predecessors: Vec<Transition>,
 }
 
-impl Default for StatePredecessors {
-    fn default() -> Self {
-        Self {
-            predecessors: Vec::new(),
-        }
-    }
-}
-
 #[derive(Copy, Clone)]
 pub struct Transition {
     source: usize,
@@ -265,3 +727,48 @@ impl Label {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // State 0 may loop or move on to 1, which leads to the bad state 2 on the counterexample
+    fn lasso_game() -> Game {
+        let successors = vec![vec![0, 1], vec![2], vec![2]];
+        let labels = vec![("x".to_string(), vec![0])];
+        let mut game = Game::from_successors(0, &successors, labels);
+        game.bad_states = vec![2];
+        game.mark_counterexample_path(vec![0, 1, 2]);
+        game
+    }
+
+    #[test]
+    fn unroll_copies_states_per_step() {
+        let (mut unrolled, copies) = lasso_game().unroll(2);
+        assert_eq!(copies, vec![vec![0, 1, 3], vec![2, 4], vec![5]]);
+        assert_eq!(unrolled.bad_states, vec![5]);
+        assert_eq!(unrolled.labels[0].states, vec![0, 1, 3]);
+        let mut transitions = unrolled.transitions();
+        transitions.sort();
+        assert_eq!(
+            transitions,
+            vec![
+                (0, 1, false),
+                (0, 2, true),
+                (1, 3, false),
+                (1, 4, true),
+                (2, 5, true),
+            ]
+        );
+        assert!(unrolled.states.iter().all(|s| s.owner == Player::Path));
+        assert_eq!(unrolled.determine_winner(), Player::Reach);
+    }
+
+    #[test]
+    fn unroll_cuts_off_plays_after_the_last_step() {
+        let (mut unrolled, copies) = lasso_game().unroll(1);
+        assert_eq!(copies, vec![vec![0, 1], vec![2], vec![]]);
+        assert!(unrolled.bad_states.is_empty());
+        assert_eq!(unrolled.determine_winner(), Player::Safe);
+    }
+}
@@ -62,7 +62,7 @@ impl SuperAttractor {
                 }
                 let new_winning = self.attract(&next_set.winning.clone().with_state(new_state));
                 if new_winning.contains(self.game.initial_state) {
-                    if !minimal_causes.iter().any(|m| *m == new_winning) {
+                    if !minimal_causes.contains(&new_winning) {
                         minimal_causes.push(new_winning);
                         // println!("    Found minimal cause: {:?}", new_controlled);
                     }
@@ -115,12 +115,11 @@ impl SuperAttractor {
 
         for &state in &winning_set.states {
             for predecessor in &self.game.state_predecessors[state].predecessors {
-                if self.game.states[predecessor.source].attractor_count > 0 {
-                    if self.game.states[predecessor.source].default_owner != Player::Path
-                        || predecessor.on_path
-                    {
-                        can_reach.push(predecessor.source);
-                    }
+                if self.game.states[predecessor.source].attractor_count > 0
+                    && (self.game.states[predecessor.source].default_owner != Player::Path
+                        || predecessor.on_path)
+                {
+                    can_reach.push(predecessor.source);
                 }
             }
         }
@@ -168,7 +167,7 @@ impl StateSet {
 
     pub fn from_states(states: Vec<usize>) -> Self {
         Self {
-            states: HashSet::from_iter(states.into_iter()),
+            states: HashSet::from_iter(states),
         }
     }
 
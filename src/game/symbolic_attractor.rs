@@ -0,0 +1,109 @@
+use crate::bdd::{Bdd, BddNode, FALSE, TRUE};
+use crate::game::{Game, Player};
+use std::collections::VecDeque;
+
+// Computes, for every state at once, the set of coalitions for which the reachability player can
+// force a visit to a bad state. Each state carries a BDD over the coalition variables that starts
+// out as false and is widened until it is stable, mirroring the attractor in `determine_winner`.
+pub struct SymbolicAttractor<'a> {
+    game: &'a Game,
+    successors: Vec<Vec<(usize, bool)>>, // Target state and whether the transition is on the path
+    coalition_membership: &'a [BddNode],
+}
+
+impl<'a> SymbolicAttractor<'a> {
+    // `coalition_membership` gives, for every state, the coalitions in which the state is
+    // controlled by the safety player.
+    pub fn new(game: &'a Game, coalition_membership: &'a [BddNode]) -> Self {
+        let mut successors = vec![Vec::new(); game.states.len()];
+        for (target, predecessors) in game.state_predecessors.iter().enumerate() {
+            for transition in &predecessors.predecessors {
+                successors[transition.source].push((target, transition.on_path));
+            }
+        }
+        Self {
+            game,
+            successors,
+            coalition_membership,
+        }
+    }
+
+    pub fn run(&self, bdd: &mut Bdd) -> Vec<BddNode> {
+        let mut attracted = vec![FALSE; self.game.states.len()];
+        let mut is_open = vec![false; self.game.states.len()];
+        let mut open_set = VecDeque::new();
+        for &bad_state in &self.game.bad_states {
+            attracted[bad_state] = TRUE;
+        }
+        for &bad_state in &self.game.bad_states {
+            self.open_predecessors(bad_state, &attracted, &mut is_open, &mut open_set);
+        }
+
+        while let Some(state) = open_set.pop_front() {
+            is_open[state] = false;
+            let updated = self.attract_state(bdd, state, &attracted);
+            if updated != attracted[state] {
+                attracted[state] = updated;
+                self.open_predecessors(state, &attracted, &mut is_open, &mut open_set);
+            }
+        }
+
+        attracted
+    }
+
+    // States that are attracted for every coalition (in particular bad states) cannot grow further
+    // and are not reopened.
+    fn open_predecessors(
+        &self,
+        state: usize,
+        attracted: &[BddNode],
+        is_open: &mut [bool],
+        open_set: &mut VecDeque<usize>,
+    ) {
+        for transition in &self.game.state_predecessors[state].predecessors {
+            let source = transition.source;
+            if !is_open[source] && attracted[source] != TRUE {
+                is_open[source] = true;
+                open_set.push_back(source);
+            }
+        }
+    }
+
+    fn attract_state(&self, bdd: &mut Bdd, state: usize, attracted: &[BddNode]) -> BddNode {
+        let successors = &self.successors[state];
+
+        // States without successors are never attracted, regardless of their owner
+        let mut all_attracted = if successors.is_empty() { FALSE } else { TRUE };
+        for &(target, _) in successors {
+            all_attracted = bdd.and(all_attracted, attracted[target]);
+        }
+
+        let default_attracted = match self.game.states[state].default_owner {
+            Player::Reach => self.any_attracted(bdd, successors, attracted, false),
+            Player::Path => self.any_attracted(bdd, successors, attracted, true),
+            Player::Safe => all_attracted,
+        };
+
+        let in_coalition = self.coalition_membership[state];
+        let not_in_coalition = bdd.not(in_coalition);
+        let attracted_in_coalition = bdd.and(in_coalition, all_attracted);
+        let attracted_otherwise = bdd.and(not_in_coalition, default_attracted);
+        bdd.or(attracted_in_coalition, attracted_otherwise)
+    }
+
+    fn any_attracted(
+        &self,
+        bdd: &mut Bdd,
+        successors: &[(usize, bool)],
+        attracted: &[BddNode],
+        only_on_path: bool,
+    ) -> BddNode {
+        let mut any_attracted = FALSE;
+        for &(target, on_path) in successors {
+            if on_path || !only_on_path {
+                any_attracted = bdd.or(any_attracted, attracted[target]);
+            }
+        }
+        any_attracted
+    }
+}
@@ -0,0 +1,206 @@
+use std::fs::File;
+use std::io::Write;
+
+pub struct GeneratorSettings {
+    pub state_count: usize,
+    pub branching: usize, // The number of successors of every state that is not bad
+    pub bad_density: f64, // The fraction of states that are bad
+    pub counterexample_length: usize, // The number of steps of the counterexample
+    pub label_count: usize, // The number of labels that are assigned to the states at random
+    pub seed: u64,
+}
+
+// A random transition system with a counterexample. State 0 is the initial state, bad states only
+// have a self-loop and every other state has a fixed number of distinct random successors. The
+// counterexample visits distinct states and only its last state is bad, so it reaches a bad state
+// in exactly the given number of steps.
+pub struct RandomModel {
+    successors: Vec<Vec<usize>>,
+    is_bad: Vec<bool>,
+    labels: Vec<Option<usize>>, // The label of every state, if labels are assigned
+    label_count: usize,
+    counterexample: Vec<usize>,
+}
+
+impl RandomModel {
+    pub fn generate(settings: &GeneratorSettings) -> Self {
+        let n = settings.state_count;
+        if settings.counterexample_length == 0 || settings.counterexample_length >= n {
+            panic!(
+                "The counterexample must have at least one step and fewer steps than there are states ({}).",
+                n
+            );
+        }
+        if settings.branching == 0 || settings.branching > n {
+            panic!(
+                "The branching factor must be between 1 and the number of states ({}).",
+                n
+            );
+        }
+        if !(0.0..=1.0).contains(&settings.bad_density) {
+            panic!("The density of bad states must be between 0 and 1.");
+        }
+        let mut rng = fastrand::Rng::with_seed(settings.seed);
+
+        // The counterexample is followed by the other bad states in a random order of all states
+        // except the initial one
+        let mut order = (1..n).collect::<Vec<_>>();
+        rng.shuffle(&mut order);
+        let length = settings.counterexample_length;
+        let mut counterexample = vec![0];
+        counterexample.extend_from_slice(&order[..length]);
+        let bad_count = ((settings.bad_density * n as f64).round() as usize).clamp(1, n - length);
+        let mut is_bad = vec![false; n];
+        for &state in &order[length - 1..length - 1 + bad_count] {
+            is_bad[state] = true;
+        }
+
+        let mut successors = vec![Vec::new(); n];
+        for (&from, &to) in counterexample.iter().zip(counterexample.iter().skip(1)) {
+            successors[from].push(to);
+        }
+        for state in 0..n {
+            if is_bad[state] {
+                successors[state].push(state);
+                continue;
+            }
+            while successors[state].len() < settings.branching {
+                let successor = rng.usize(..n);
+                if !successors[state].contains(&successor) {
+                    successors[state].push(successor);
+                }
+            }
+            successors[state].sort();
+        }
+
+        let labels = (0..n)
+            .map(|_| (settings.label_count > 0).then(|| rng.usize(..settings.label_count)))
+            .collect();
+
+        Self {
+            successors,
+            is_bad,
+            labels,
+            label_count: settings.label_count,
+            counterexample,
+        }
+    }
+
+    pub fn transition_count(&self) -> usize {
+        self.successors.iter().map(Vec::len).sum()
+    }
+
+    pub fn bad_count(&self) -> usize {
+        self.is_bad.iter().filter(|&&is_bad| is_bad).count()
+    }
+
+    // Writes the model to "<stem>.sta", "<stem>.tra" and "<stem>.lab" in the format of PRISM's
+    // exports, where the bad states have the given label and the other labels are named "l1",
+    // "l2", etc. The counterexample is written to "<stem>.ce".
+    pub fn write(&self, stem: &str, bad_label: &str) {
+        let mut states = String::from("(s)\n");
+        for state in 0..self.successors.len() {
+            states += &format!("{}:({})\n", state, state);
+        }
+        write_file(&format!("{}.sta", stem), &states);
+
+        let transition_count = self.transition_count();
+        let mut transitions = format!(
+            "{} {} {}\n",
+            self.successors.len(),
+            transition_count,
+            transition_count
+        );
+        for (state, successors) in self.successors.iter().enumerate() {
+            for (choice, successor) in successors.iter().enumerate() {
+                transitions += &format!("{} {} {} 1\n", state, choice, successor);
+            }
+        }
+        write_file(&format!("{}.tra", stem), &transitions);
+
+        let mut labels = format!("0=\"init\" 1=\"{}\"", bad_label);
+        for label in 0..self.label_count {
+            labels += &format!(" {}=\"l{}\"", label + 2, label + 1);
+        }
+        labels += "\n";
+        for state in 0..self.successors.len() {
+            let mut indices = Vec::new();
+            if state == 0 {
+                indices.push(0);
+            }
+            if self.is_bad[state] {
+                indices.push(1);
+            }
+            if let Some(label) = self.labels[state] {
+                indices.push(label + 2);
+            }
+            if !indices.is_empty() {
+                let indices = indices.iter().map(|i| i.to_string()).collect::<Vec<_>>();
+                labels += &format!("{}: {}\n", state, indices.join(" "));
+            }
+        }
+        write_file(&format!("{}.lab", stem), &labels);
+
+        let counterexample = self
+            .counterexample
+            .iter()
+            .map(|state| format!("({})\n", state))
+            .collect::<String>();
+        write_file(&format!("{}.ce", stem), &counterexample);
+    }
+}
+
+fn write_file(file_name: &str, content: &str) {
+    File::create(file_name)
+        .and_then(|mut file| file.write_all(content.as_bytes()))
+        .unwrap_or_else(|e| panic!("Could not write \"{}\": {}", file_name, e));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn settings(seed: u64) -> GeneratorSettings {
+        GeneratorSettings {
+            state_count: 20,
+            branching: 3,
+            bad_density: 0.2,
+            counterexample_length: 5,
+            label_count: 2,
+            seed,
+        }
+    }
+
+    #[test]
+    fn same_seed_gives_the_same_model() {
+        let first = RandomModel::generate(&settings(7));
+        let second = RandomModel::generate(&settings(7));
+        assert_eq!(first.successors, second.successors);
+        assert_eq!(first.is_bad, second.is_bad);
+        assert_eq!(first.labels, second.labels);
+        assert_eq!(first.counterexample, second.counterexample);
+
+        let other = RandomModel::generate(&settings(8));
+        assert_ne!(first.successors, other.successors);
+    }
+
+    #[test]
+    fn counterexample_reaches_a_bad_state_in_the_given_steps() {
+        let model = RandomModel::generate(&settings(7));
+        let counterexample = &model.counterexample;
+        assert_eq!(counterexample.len(), 6);
+        assert_eq!(counterexample[0], 0);
+        for (&from, &to) in counterexample.iter().zip(counterexample.iter().skip(1)) {
+            assert!(model.successors[from].contains(&to));
+        }
+        let bad_on_counterexample = counterexample.iter().filter(|&&s| model.is_bad[s]).count();
+        assert_eq!(bad_on_counterexample, 1);
+        assert!(model.is_bad[counterexample[5]]);
+        assert_eq!(model.bad_count(), 4);
+
+        for (state, successors) in model.successors.iter().enumerate() {
+            let expected = if model.is_bad[state] { 1 } else { 3 };
+            assert_eq!(successors.len(), expected);
+        }
+    }
+}
@@ -0,0 +1,465 @@
+use crate::transition_systems::TransitionSystem;
+
+// An LTL formula as written by the user. Atomic propositions are indices into the list of
+// propositions that is returned by the parser.
+enum Ltl {
+    True,
+    False,
+    Proposition(usize),
+    Not(Box<Ltl>),
+    And(Box<Ltl>, Box<Ltl>),
+    Or(Box<Ltl>, Box<Ltl>),
+    Implies(Box<Ltl>, Box<Ltl>),
+    Equivalent(Box<Ltl>, Box<Ltl>),
+    Next(Box<Ltl>),
+    Finally(Box<Ltl>),
+    Globally(Box<Ltl>),
+    Until(Box<Ltl>, Box<Ltl>),
+    Release(Box<Ltl>, Box<Ltl>),
+    WeakUntil(Box<Ltl>, Box<Ltl>),
+}
+
+// A node of a formula in negation normal form, where negations only occur in literals and all
+// temporal operators are expressed by next, until and release. Children are indices of nodes.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Node {
+    True,
+    False,
+    Literal(usize, bool), // Proposition and whether it occurs positively
+    And(usize, usize),
+    Or(usize, usize),
+    Next(usize),
+    Until(usize, usize),
+    Release(usize, usize),
+}
+
+// A formula in negation normal form, stored as a list of nodes whose children precede them
+pub struct Formula {
+    pub nodes: Vec<Node>,
+    pub root: usize,
+    // For every proposition, whether it holds in each state of the transition system
+    pub propositions: Vec<Vec<bool>>,
+}
+
+impl Formula {
+    // Parses an LTL formula over the labels and variables of the transition system and returns its
+    // negation in negation normal form. Atomic propositions are label names (in quotes if they
+    // collide with an operator), "variable=value", "variable!=value", "true" and "false".
+    // Operators are "!", "&", "|", "->", "<->", "X", "F", "G", "U", "R" and "W", where unary
+    // operators bind strongest, followed by the binary temporal operators, "&", "|", "->" and
+    // "<->".
+    pub fn parse_negated(formula: &str, ts: &TransitionSystem) -> Self {
+        let mut parser = Parser {
+            formula,
+            chars: formula.chars().collect(),
+            position: 0,
+            ts,
+            propositions: Vec::new(),
+        };
+        let ltl = parser.parse_equivalence();
+        parser.skip_whitespace();
+        if parser.position < parser.chars.len() {
+            parser.error("unexpected input after the end of the formula");
+        }
+
+        let mut negated = Formula {
+            nodes: Vec::new(),
+            root: 0,
+            propositions: parser.propositions,
+        };
+        negated.root = negated.add_negation_normal_form(&ltl, true);
+        negated
+    }
+
+    fn add(&mut self, node: Node) -> usize {
+        match self.nodes.iter().position(|&existing| existing == node) {
+            Some(index) => index,
+            None => {
+                self.nodes.push(node);
+                self.nodes.len() - 1
+            }
+        }
+    }
+
+    fn add_negation_normal_form(&mut self, ltl: &Ltl, negated: bool) -> usize {
+        let node = match ltl {
+            Ltl::True if negated => Node::False,
+            Ltl::True => Node::True,
+            Ltl::False if negated => Node::True,
+            Ltl::False => Node::False,
+            Ltl::Proposition(proposition) => Node::Literal(*proposition, !negated),
+            Ltl::Not(inner) => return self.add_negation_normal_form(inner, !negated),
+            Ltl::And(left, right) | Ltl::Or(left, right) => {
+                let left = self.add_negation_normal_form(left, negated);
+                let right = self.add_negation_normal_form(right, negated);
+                if matches!(ltl, Ltl::And(..)) != negated {
+                    Node::And(left, right)
+                } else {
+                    Node::Or(left, right)
+                }
+            }
+            Ltl::Implies(left, right) => {
+                // a -> b is !a | b, and its negation is a & !b
+                let left = self.add_negation_normal_form(left, !negated);
+                let right = self.add_negation_normal_form(right, negated);
+                if negated {
+                    Node::And(left, right)
+                } else {
+                    Node::Or(left, right)
+                }
+            }
+            Ltl::Equivalent(left, right) => {
+                // a <-> b is (a & b) | (!a & !b), and its negation is (a & !b) | (!a & b)
+                let positive_left = self.add_negation_normal_form(left, false);
+                let negative_left = self.add_negation_normal_form(left, true);
+                let positive_right = self.add_negation_normal_form(right, false);
+                let negative_right = self.add_negation_normal_form(right, true);
+                let (first, second) = if negated {
+                    (
+                        Node::And(positive_left, negative_right),
+                        Node::And(negative_left, positive_right),
+                    )
+                } else {
+                    (
+                        Node::And(positive_left, positive_right),
+                        Node::And(negative_left, negative_right),
+                    )
+                };
+                let first = self.add(first);
+                let second = self.add(second);
+                Node::Or(first, second)
+            }
+            Ltl::Next(inner) => Node::Next(self.add_negation_normal_form(inner, negated)),
+            Ltl::Finally(inner) | Ltl::Globally(inner) => {
+                // F a is true U a and G a is false R a, which are dual to each other
+                let inner = self.add_negation_normal_form(inner, negated);
+                if matches!(ltl, Ltl::Finally(_)) != negated {
+                    Node::Until(self.add(Node::True), inner)
+                } else {
+                    Node::Release(self.add(Node::False), inner)
+                }
+            }
+            Ltl::Until(left, right) | Ltl::Release(left, right) => {
+                let left = self.add_negation_normal_form(left, negated);
+                let right = self.add_negation_normal_form(right, negated);
+                if matches!(ltl, Ltl::Until(..)) != negated {
+                    Node::Until(left, right)
+                } else {
+                    Node::Release(left, right)
+                }
+            }
+            Ltl::WeakUntil(left, right) => {
+                // a W b is b R (a | b), and its negation is !b U (!a & !b)
+                let left = self.add_negation_normal_form(left, negated);
+                let right = self.add_negation_normal_form(right, negated);
+                if negated {
+                    let both = self.add(Node::And(left, right));
+                    Node::Until(right, both)
+                } else {
+                    let either = self.add(Node::Or(left, right));
+                    Node::Release(right, either)
+                }
+            }
+        };
+        self.add(node)
+    }
+}
+
+struct Parser<'a> {
+    formula: &'a str,
+    chars: Vec<char>,
+    position: usize,
+    ts: &'a TransitionSystem,
+    propositions: Vec<Vec<bool>>,
+}
+
+impl Parser<'_> {
+    fn error(&self, message: &str) -> ! {
+        panic!(
+            "Could not parse LTL formula \"{}\" at position {}: {}.",
+            self.formula, self.position, message
+        )
+    }
+
+    fn skip_whitespace(&mut self) {
+        while self.position < self.chars.len() && self.chars[self.position].is_whitespace() {
+            self.position += 1;
+        }
+    }
+
+    // Consumes the given operator (after whitespace) if it comes next
+    fn accept(&mut self, operator: &str) -> bool {
+        self.skip_whitespace();
+        let operator = operator.chars().collect::<Vec<_>>();
+        if !self.chars[self.position..].starts_with(&operator) {
+            return false;
+        }
+        // Letter operators must not be the start of a longer name, e.g. "Fail"
+        let end = self.position + operator.len();
+        if operator[0].is_alphabetic() && end < self.chars.len() && is_name_char(self.chars[end]) {
+            return false;
+        }
+        self.position = end;
+        true
+    }
+
+    fn parse_equivalence(&mut self) -> Ltl {
+        let mut left = self.parse_implication();
+        while self.accept("<->") {
+            let right = self.parse_implication();
+            left = Ltl::Equivalent(Box::new(left), Box::new(right));
+        }
+        left
+    }
+
+    fn parse_implication(&mut self) -> Ltl {
+        let left = self.parse_disjunction();
+        if self.accept("->") {
+            let right = self.parse_implication();
+            return Ltl::Implies(Box::new(left), Box::new(right));
+        }
+        left
+    }
+
+    fn parse_disjunction(&mut self) -> Ltl {
+        let mut left = self.parse_conjunction();
+        while self.accept("||") || self.accept("|") {
+            let right = self.parse_conjunction();
+            left = Ltl::Or(Box::new(left), Box::new(right));
+        }
+        left
+    }
+
+    fn parse_conjunction(&mut self) -> Ltl {
+        let mut left = self.parse_binary_temporal();
+        while self.accept("&&") || self.accept("&") {
+            let right = self.parse_binary_temporal();
+            left = Ltl::And(Box::new(left), Box::new(right));
+        }
+        left
+    }
+
+    fn parse_binary_temporal(&mut self) -> Ltl {
+        let left = self.parse_unary();
+        if self.accept("U") {
+            Ltl::Until(Box::new(left), Box::new(self.parse_binary_temporal()))
+        } else if self.accept("R") {
+            Ltl::Release(Box::new(left), Box::new(self.parse_binary_temporal()))
+        } else if self.accept("W") {
+            Ltl::WeakUntil(Box::new(left), Box::new(self.parse_binary_temporal()))
+        } else {
+            left
+        }
+    }
+
+    fn parse_unary(&mut self) -> Ltl {
+        if self.accept("!") {
+            Ltl::Not(Box::new(self.parse_unary()))
+        } else if self.accept("X") {
+            Ltl::Next(Box::new(self.parse_unary()))
+        } else if self.accept("F") {
+            Ltl::Finally(Box::new(self.parse_unary()))
+        } else if self.accept("G") {
+            Ltl::Globally(Box::new(self.parse_unary()))
+        } else if self.accept("(") {
+            let inner = self.parse_equivalence();
+            if !self.accept(")") {
+                self.error("expected \")\"");
+            }
+            inner
+        } else {
+            self.parse_atom()
+        }
+    }
+
+    fn parse_name(&mut self) -> String {
+        self.skip_whitespace();
+        let start = self.position;
+        while self.position < self.chars.len() && is_name_char(self.chars[self.position]) {
+            self.position += 1;
+        }
+        if start == self.position {
+            self.error("expected a proposition");
+        }
+        self.chars[start..self.position].iter().collect()
+    }
+
+    fn parse_atom(&mut self) -> Ltl {
+        if self.accept("\"") {
+            let start = self.position;
+            while self.position < self.chars.len() && self.chars[self.position] != '"' {
+                self.position += 1;
+            }
+            let label = self.chars[start..self.position].iter().collect::<String>();
+            if !self.accept("\"") {
+                self.error("expected closing quote");
+            }
+            return self.label_proposition(&label);
+        }
+
+        let name = self.parse_name();
+        let is_negated = if self.accept("!=") {
+            true
+        } else if self.accept("=") {
+            false
+        } else {
+            return match name.as_str() {
+                "true" => Ltl::True,
+                "false" => Ltl::False,
+                _ => self.label_proposition(&name),
+            };
+        };
+
+        // Values may be negative numbers
+        let sign = if self.accept("-") { "-" } else { "" };
+        let value = format!("{}{}", sign, self.parse_name());
+        let variable = self
+            .ts
+            .get_variable_index(&name)
+            .unwrap_or_else(|| self.error(&format!("unknown variable \"{}\"", name)));
+        let value = self.ts.variables[variable]
+            .get_valuation_index(&value)
+            .unwrap_or_else(|| {
+                self.error(&format!(
+                    "unknown value \"{}\" for variable \"{}\"",
+                    value, name
+                ))
+            });
+        let values = self
+            .ts
+            .states
+            .iter()
+            .map(|state| (state.valuation_indices[variable] == value) != is_negated)
+            .collect();
+        self.add_proposition(values)
+    }
+
+    fn label_proposition(&mut self, label: &str) -> Ltl {
+        // The parser of the model does not store the initial label with the states
+        if label == "init" {
+            let values = (0..self.ts.states.len())
+                .map(|state| state == self.ts.initial_state)
+                .collect();
+            return self.add_proposition(values);
+        }
+        let label_index = self
+            .ts
+            .label_names
+            .iter()
+            .find(|(_, name)| name == label)
+            .map(|&(index, _)| index)
+            .unwrap_or_else(|| self.error(&format!("unknown label \"{}\"", label)));
+        let values = self
+            .ts
+            .states
+            .iter()
+            .map(|state| state.labels.contains(&label_index))
+            .collect();
+        self.add_proposition(values)
+    }
+
+    fn add_proposition(&mut self, values: Vec<bool>) -> Ltl {
+        self.propositions.push(values);
+        Ltl::Proposition(self.propositions.len() - 1)
+    }
+}
+
+fn is_name_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '.'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn negated(formula: &str) -> Formula {
+        let ts = TransitionSystem::from_successors(
+            &[vec![1], vec![2], vec![2]],
+            &[
+                ("a", vec![0]),
+                ("b", vec![1]),
+                ("c", vec![2]),
+                ("d", vec![]),
+            ],
+        );
+        Formula::parse_negated(formula, &ts)
+    }
+
+    // Writes the node with propositions named by their index
+    fn show(formula: &Formula, node: usize) -> String {
+        match formula.nodes[node] {
+            Node::True => "true".to_string(),
+            Node::False => "false".to_string(),
+            Node::Literal(proposition, true) => format!("p{}", proposition),
+            Node::Literal(proposition, false) => format!("!p{}", proposition),
+            Node::And(left, right) => {
+                format!("({} & {})", show(formula, left), show(formula, right))
+            }
+            Node::Or(left, right) => {
+                format!("({} | {})", show(formula, left), show(formula, right))
+            }
+            Node::Next(inner) => format!("X {}", show(formula, inner)),
+            Node::Until(left, right) => {
+                format!("({} U {})", show(formula, left), show(formula, right))
+            }
+            Node::Release(left, right) => {
+                format!("({} R {})", show(formula, left), show(formula, right))
+            }
+        }
+    }
+
+    fn show_negated(formula: &str) -> String {
+        let formula = negated(formula);
+        show(&formula, formula.root)
+    }
+
+    #[test]
+    fn negation_normal_form() {
+        assert_eq!(show_negated("a W b"), "(!p1 U (!p0 & !p1))");
+        assert_eq!(show_negated("!(a W b)"), "(p1 R (p0 | p1))");
+        assert_eq!(show_negated("a <-> b"), "((p0 & !p1) | (!p0 & p1))");
+        assert_eq!(show_negated("!(a <-> b)"), "((p0 & p1) | (!p0 & !p1))");
+        assert_eq!(show_negated("!(a -> X b)"), "(!p0 | X p1)");
+        assert_eq!(show_negated("G F a"), "(true U (false R !p0))");
+        assert_eq!(show_negated("a U b R c"), "(!p0 R (!p1 U !p2))");
+    }
+
+    #[test]
+    fn precedence() {
+        assert_eq!(show_negated("a & b U c | d"), "((!p0 | (!p1 R !p2)) & !p3)");
+        assert_eq!(
+            show_negated("a -> b <-> c"),
+            "(((!p0 | p1) & !p2) | ((p0 & !p1) & p2))"
+        );
+        assert_eq!(show_negated("!X (a)"), "X p0");
+    }
+
+    #[test]
+    fn propositions() {
+        let formula = negated("s=1 | s!=2 | \"init\" | true");
+        assert_eq!(
+            formula.propositions,
+            vec![
+                vec![false, true, false],
+                vec![true, true, false],
+                vec![true, false, false],
+            ]
+        );
+        assert_eq!(
+            show(&formula, formula.root),
+            "(((!p0 & !p1) & !p2) & false)"
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown label \"e\"")]
+    fn unknown_labels_are_rejected() {
+        negated("a U e");
+    }
+
+    #[test]
+    #[should_panic(expected = "unexpected input after the end of the formula")]
+    fn trailing_input_is_rejected() {
+        negated("a b");
+    }
+}
@@ -0,0 +1,4 @@
+mod formula;
+mod product;
+
+pub use product::LtlProduct;
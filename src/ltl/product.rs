@@ -0,0 +1,542 @@
+use crate::game::{Game, Objective};
+use crate::ltl::formula::{Formula, Node};
+use crate::transition_systems::TransitionSystem;
+use std::collections::{HashMap, VecDeque};
+
+// The largest number of temporal operators, as the automaton has 2^n states for n operators
+const MAX_TEMPORAL_OPERATORS: usize = 16;
+
+// A Büchi automaton for the negation of an LTL formula, built by the tableau construction. A state
+// (an "atom") is the set of next-obligations, i.e. of the subformulas X a, X (a U b) and X (a R b)
+// that must hold in the next step. Together with the propositions of a model state, an atom
+// determines which subformulas hold in that state. The automaton moves from atom A to atom A' in
+// model state s' iff all obligations of A hold in s' and A'. Obligations that are not required
+// may be dropped, which is sound as formulas in negation normal form only hold in more states
+// with more obligations.
+//
+// A run is accepting iff every until that it is obliged to is eventually fulfilled. If no until
+// occurs within a release, a word only requires finitely many untils to be fulfilled, so it is
+// accepted iff a run eventually stays in atoms without until obligations ("settled" atoms). This
+// co-Büchi condition can be checked deterministically by the breakpoint construction, which is
+// needed for the game, as a nondeterministic automaton would have to guess how the play continues.
+struct Tableau {
+    formula: Formula,
+    obligation_bits: Vec<Option<usize>>, // For every node, the bit of its next-obligation
+    until_bits: u32,                     // The bits of the obligations of untils
+}
+
+// A state of the automaton after the breakpoint construction. `atoms` are the atoms that a run can
+// be in, `settled` are those that a run can be in that stayed in settled atoms since the last
+// breakpoint. At a breakpoint, where no such run is left, all settled atoms are tracked again. A
+// word is accepted iff there are only finitely many breakpoints.
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct MacroState {
+    atoms: Vec<u32>,
+    settled: Vec<u32>,
+}
+
+impl MacroState {
+    fn is_breakpoint(&self) -> bool {
+        self.settled.is_empty()
+    }
+}
+
+impl Tableau {
+    fn new(formula: Formula) -> Self {
+        let mut obligation_bits = vec![None; formula.nodes.len()];
+        let mut until_bits = 0;
+        let mut bit_count = 0;
+        // Children precede their parents, so whether a subformula contains an until is known for
+        // all children of a node
+        let mut contains_until = vec![false; formula.nodes.len()];
+        for (index, node) in formula.nodes.iter().enumerate() {
+            contains_until[index] = match *node {
+                Node::True | Node::False | Node::Literal(..) => false,
+                Node::Next(inner) => contains_until[inner],
+                Node::Until(..) => true,
+                Node::And(left, right) | Node::Or(left, right) | Node::Release(left, right) => {
+                    contains_until[left] || contains_until[right]
+                }
+            };
+            if let Node::Release(left, right) = *node {
+                if contains_until[left] || contains_until[right] {
+                    panic!("LTL formulas in which G, R or W occurs within F or U (after moving negations inwards), e.g. \"F G a\", are not supported.");
+                }
+            }
+            if let Node::Next(_) | Node::Until(..) | Node::Release(..) = node {
+                if let Node::Until(..) = node {
+                    until_bits |= 1 << bit_count;
+                }
+                obligation_bits[index] = Some(bit_count);
+                bit_count += 1;
+            }
+        }
+        if bit_count > MAX_TEMPORAL_OPERATORS {
+            panic!(
+                "The LTL formula has {} temporal operators, but at most {} are supported.",
+                bit_count, MAX_TEMPORAL_OPERATORS
+            );
+        }
+        Self {
+            formula,
+            obligation_bits,
+            until_bits,
+        }
+    }
+
+    fn atom_count(&self) -> u32 {
+        1 << self.obligation_bits.iter().flatten().count()
+    }
+
+    fn holds(&self, node: usize, state: usize, atom: u32) -> bool {
+        let obliged = || atom & 1 << self.obligation_bits[node].unwrap() != 0;
+        match self.formula.nodes[node] {
+            Node::True => true,
+            Node::False => false,
+            Node::Literal(proposition, positive) => {
+                self.formula.propositions[proposition][state] == positive
+            }
+            Node::And(left, right) => {
+                self.holds(left, state, atom) && self.holds(right, state, atom)
+            }
+            Node::Or(left, right) => {
+                self.holds(left, state, atom) || self.holds(right, state, atom)
+            }
+            Node::Next(_) => obliged(),
+            Node::Until(left, right) => {
+                self.holds(right, state, atom) || self.holds(left, state, atom) && obliged()
+            }
+            Node::Release(left, right) => {
+                self.holds(right, state, atom) && (self.holds(left, state, atom) || obliged())
+            }
+        }
+    }
+
+    // Returns, for every atom in the given model state, the obligations that hold in it. The
+    // automaton can move from atom A to the atoms whose obligations include A.
+    fn successor_atoms(&self, state: usize) -> Vec<(u32, u32)> {
+        let mut successors = Vec::new();
+        for atom in 0..self.atom_count() {
+            let mut obligations = 0;
+            for (node, bit) in self.obligation_bits.iter().enumerate() {
+                let Some(bit) = bit else { continue };
+                let obligation = match self.formula.nodes[node] {
+                    Node::Next(inner) => inner,
+                    _ => node,
+                };
+                if self.holds(obligation, state, atom) {
+                    obligations |= 1 << bit;
+                }
+            }
+            successors.push((obligations, atom));
+        }
+        successors
+    }
+
+    fn settled(&self, atoms: &[u32]) -> Vec<u32> {
+        atoms
+            .iter()
+            .copied()
+            .filter(|&atom| atom & self.until_bits == 0)
+            .collect()
+    }
+
+    fn initial_macro_state(&self, state: usize) -> MacroState {
+        let atoms = (0..self.atom_count())
+            .filter(|&atom| self.holds(self.formula.root, state, atom))
+            .collect::<Vec<_>>();
+        MacroState {
+            settled: self.settled(&atoms),
+            atoms,
+        }
+    }
+
+    // Moves on to the model state whose `successor_atoms` are given
+    fn next_macro_state(
+        &self,
+        macro_state: &MacroState,
+        successor_atoms: &[(u32, u32)],
+    ) -> MacroState {
+        let step = |atoms: &[u32]| {
+            successor_atoms
+                .iter()
+                .filter(|&&(obligations, _)| atoms.iter().any(|&atom| atom & !obligations == 0))
+                .map(|&(_, atom)| atom)
+                .collect::<Vec<_>>()
+        };
+        let atoms = step(&macro_state.atoms);
+        let settled = if macro_state.is_breakpoint() {
+            self.settled(&atoms)
+        } else {
+            self.settled(&step(&macro_state.settled))
+        };
+        MacroState { atoms, settled }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+struct ProductNode {
+    state: usize,
+    macro_state: usize, // Index into the macro states of the automaton
+}
+
+// The product of a transition system with the deterministic automaton for the violations of an LTL
+// formula, as a game in which the reachability player wins by violating the formula, i.e. by
+// visiting breakpoints only finitely often. As the automaton is deterministic, the owner of a model
+// state controls all product states with this model state, which are grouped together to compute
+// its responsibility. Paths into states without successors never violate the formula, as it is
+// evaluated on infinite paths, so these states count as breakpoints.
+pub struct LtlProduct {
+    game: Game,
+    nodes: Vec<ProductNode>,
+    successors: Vec<Vec<usize>>,
+    breakpoints: Vec<bool>,
+    model_nodes: Vec<Vec<usize>>, // For every model state, its product states
+    tableau: Tableau,
+}
+
+impl LtlProduct {
+    pub fn new(ts: &TransitionSystem, formula: &str) -> Self {
+        let tableau = Tableau::new(Formula::parse_negated(formula, ts));
+        let mut successor_atoms = vec![None; ts.states.len()];
+        let mut macro_states = vec![tableau.initial_macro_state(ts.initial_state)];
+        let mut macro_state_indices = HashMap::from([(macro_states[0].clone(), 0)]);
+
+        let initial = ProductNode {
+            state: ts.initial_state,
+            macro_state: 0,
+        };
+        let mut nodes = vec![initial];
+        let mut indices = HashMap::from([(initial, 0)]);
+        let mut successors = Vec::new();
+        let mut next_open = 0;
+        while next_open < nodes.len() {
+            let ProductNode { state, macro_state } = nodes[next_open];
+            let mut targets = ts.states[state]
+                .outgoing_transitions
+                .iter()
+                .map(|transition| transition.destination)
+                .collect::<Vec<_>>();
+            targets.sort();
+            targets.dedup();
+            let mut node_successors = Vec::with_capacity(targets.len());
+            for target in targets {
+                let next = tableau.next_macro_state(
+                    &macro_states[macro_state],
+                    successor_atoms[target].get_or_insert_with(|| tableau.successor_atoms(target)),
+                );
+                let next = *macro_state_indices.entry(next).or_insert_with_key(|next| {
+                    macro_states.push(next.clone());
+                    macro_states.len() - 1
+                });
+                let node = ProductNode {
+                    state: target,
+                    macro_state: next,
+                };
+                node_successors.push(*indices.entry(node).or_insert_with(|| {
+                    nodes.push(node);
+                    nodes.len() - 1
+                }));
+            }
+            successors.push(node_successors);
+            next_open += 1;
+        }
+
+        let mut model_nodes = vec![Vec::new(); ts.states.len()];
+        let mut breakpoints = vec![false; nodes.len()];
+        for (index, node) in nodes.iter().enumerate() {
+            model_nodes[node.state].push(index);
+            breakpoints[index] =
+                macro_states[node.macro_state].is_breakpoint() || successors[index].is_empty();
+        }
+
+        // The labels of the model are lifted to the product, so that states can be grouped by them
+        let labels = Game::from_transition_system(ts)
+            .labels
+            .into_iter()
+            .map(|label| {
+                let states = Self::lift_states(&model_nodes, &label.states);
+                (label.name, states)
+            })
+            .collect();
+        let mut game = Game::from_successors(0, &successors, labels);
+        game.objective = Objective::AvoidRecurrence(
+            (0..nodes.len()).filter(|&node| breakpoints[node]).collect(),
+        );
+
+        Self {
+            game,
+            nodes,
+            successors,
+            breakpoints,
+            model_nodes,
+            tableau,
+        }
+    }
+
+    pub fn game(&self) -> &Game {
+        &self.game
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.nodes.len()
+    }
+
+    fn lift_states(model_nodes: &[Vec<usize>], states: &[usize]) -> Vec<usize> {
+        states
+            .iter()
+            .flat_map(|&state| model_nodes[state].iter().copied())
+            .collect()
+    }
+
+    // Lifts groups of model states, e.g. custom groups, to groups of product states
+    pub fn lift_groups(&self, groups: &[(String, Vec<usize>)]) -> Vec<(String, Vec<usize>)> {
+        groups
+            .iter()
+            .map(|(name, states)| (name.clone(), Self::lift_states(&self.model_nodes, states)))
+            .collect()
+    }
+
+    // For every model state, its product states
+    pub fn model_nodes(&self) -> &[Vec<usize>] {
+        &self.model_nodes
+    }
+
+    // Restricts all product states of the states on the counterexample to the transitions of the
+    // counterexample, independently of the state of the automaton
+    pub fn mark_counterexample(&self, game: &mut Game, counterexample: &[usize]) {
+        let states = Self::lift_states(&self.model_nodes, counterexample);
+        let mut transitions = Vec::new();
+        for (&from, &to) in counterexample.iter().zip(counterexample.iter().skip(1)) {
+            for &node in &self.model_nodes[from] {
+                for &successor in &self.successors[node] {
+                    if self.nodes[successor].state == to {
+                        transitions.push((node, successor));
+                    }
+                }
+            }
+        }
+        game.mark_path_transitions(&states, &transitions);
+    }
+
+    // Whether the lasso (see `TransitionSystem::verify_lasso`) violates the formula. As the
+    // automaton is deterministic, its run on the lasso eventually repeats, and the lasso is
+    // accepted iff the repeated part contains no breakpoint.
+    pub fn accepts(&self, counterexample: &[usize]) -> bool {
+        let position_count = counterexample.len() - 1;
+        let last = counterexample[position_count];
+        let Some(cycle_start) = counterexample[..position_count]
+            .iter()
+            .position(|&state| state == last)
+        else {
+            return false;
+        };
+        let successor_atoms = counterexample[..position_count]
+            .iter()
+            .map(|&state| self.tableau.successor_atoms(state))
+            .collect::<Vec<_>>();
+
+        let mut first_steps = HashMap::new();
+        let mut breakpoints = Vec::new();
+        let mut position = 0;
+        let mut macro_state = self.tableau.initial_macro_state(counterexample[0]);
+        loop {
+            if let Some(&first_step) = first_steps.get(&(position, macro_state.clone())) {
+                return !breakpoints[first_step..].contains(&true);
+            }
+            first_steps.insert((position, macro_state.clone()), breakpoints.len());
+            breakpoints.push(macro_state.is_breakpoint());
+            position = if position + 1 < position_count {
+                position + 1
+            } else {
+                cycle_start
+            };
+            macro_state = self
+                .tableau
+                .next_macro_state(&macro_state, &successor_atoms[position]);
+        }
+    }
+
+    // Returns a lasso of the model that violates the formula, or None if the formula holds. The
+    // cycle of the lasso must start in a model state that occurs nowhere else on the lasso, as
+    // lassos are closed at the first occurrence of their last state.
+    pub fn find_counterexample(&self) -> Option<Vec<usize>> {
+        let path = find_lasso(&self.successors, &self.breakpoints, |cycle_start, node| {
+            node != cycle_start && self.nodes[node].state == self.nodes[cycle_start].state
+        })?;
+        Some(
+            path.into_iter()
+                .map(|node| self.nodes[node].state)
+                .collect(),
+        )
+    }
+}
+
+// Searches for a path from the initial node 0 into a cycle without breakpoints. The path ends with
+// the first node of the cycle. The cycle starts with the first node (in the order of a
+// breadth-first search from the initial node) for which such a cycle exists, which keeps the
+// prefix short. `is_blocked(cycle_start, node)` excludes nodes from both the prefix and the cycle.
+fn find_lasso(
+    successors: &[Vec<usize>],
+    breakpoints: &[bool],
+    is_blocked: impl Fn(usize, usize) -> bool,
+) -> Option<Vec<usize>> {
+    let node_count = successors.len();
+    let mut predecessors = vec![Vec::new(); node_count];
+    for (source, targets) in successors.iter().enumerate() {
+        for &target in targets {
+            predecessors[target].push(source);
+        }
+    }
+
+    let mut order = Vec::new();
+    let mut visited = vec![false; node_count];
+    visited[0] = true;
+    let mut queue = VecDeque::from([0]);
+    while let Some(node) = queue.pop_front() {
+        order.push(node);
+        for &successor in &successors[node] {
+            if !visited[successor] {
+                visited[successor] = true;
+                queue.push_back(successor);
+            }
+        }
+    }
+
+    for cycle_start in order {
+        if breakpoints[cycle_start] {
+            continue;
+        }
+        let is_allowed = |node: usize| !is_blocked(cycle_start, node);
+        let prefix_parents = breadth_first_parents(&[0], successors, is_allowed);
+        if prefix_parents[cycle_start].is_none() {
+            continue;
+        }
+        // Searching backwards from the start of the cycle finds the shortest way back to it
+        let backward = breadth_first_parents(&[cycle_start], &predecessors, |node| {
+            is_allowed(node) && !breakpoints[node]
+        });
+        let Some(closing) = successors[cycle_start]
+            .iter()
+            .copied()
+            .filter(|&next| backward[next].is_some())
+            .min_by_key(|&next| trace_back(&backward, next).len())
+        else {
+            continue;
+        };
+
+        let mut path = trace_back(&prefix_parents, cycle_start);
+        path.extend(trace_back(&backward, closing).into_iter().rev());
+        return Some(path);
+    }
+    None
+}
+
+// Returns, for every node, the node from which a breadth-first search over the given edges
+// discovered it. The start nodes are their own parents, unreachable nodes have no parent.
+fn breadth_first_parents(
+    start: &[usize],
+    edges: &[Vec<usize>],
+    is_allowed: impl Fn(usize) -> bool + Copy,
+) -> Vec<Option<usize>> {
+    let mut parents = vec![None; edges.len()];
+    let mut queue = VecDeque::new();
+    for &node in start {
+        if is_allowed(node) && parents[node].is_none() {
+            parents[node] = Some(node);
+            queue.push_back(node);
+        }
+    }
+    while let Some(node) = queue.pop_front() {
+        for &next in &edges[node] {
+            if parents[next].is_none() && is_allowed(next) {
+                parents[next] = Some(node);
+                queue.push_back(next);
+            }
+        }
+    }
+    parents
+}
+
+// Follows the parents from the node back to a start node and returns the path from there
+fn trace_back(parents: &[Option<usize>], node: usize) -> Vec<usize> {
+    let mut path = vec![node];
+    while let Some(parent) = parents[*path.last().unwrap()] {
+        if parent == *path.last().unwrap() {
+            break;
+        }
+        path.push(parent);
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::game::Player;
+
+    // From state 1, the model moves either to state 2, where a does not hold, or to state 3, where
+    // b does not hold. Both stay there forever.
+    fn choice_model() -> TransitionSystem {
+        TransitionSystem::from_successors(
+            &[vec![1], vec![2, 3], vec![2], vec![3]],
+            &[("a", vec![0, 1, 3]), ("b", vec![0, 1, 2])],
+        )
+    }
+
+    fn winner_with_coalition(product: &LtlProduct, states: &[usize]) -> Player {
+        let mut game = product.game().clone();
+        for &state in states {
+            for &node in &product.model_nodes()[state] {
+                game.add_to_coalition(node);
+            }
+        }
+        game.determine_winner()
+    }
+
+    #[test]
+    fn violation_does_not_depend_on_guessing_the_future() {
+        // Both successors of state 1 violate the formula, so state 1 cannot prevent a violation,
+        // even though the violated conjunct depends on its choice
+        let product = LtlProduct::new(&choice_model(), "G a & G b");
+        assert_eq!(winner_with_coalition(&product, &[1]), Player::Reach);
+
+        let product = LtlProduct::new(&choice_model(), "G a");
+        assert_eq!(winner_with_coalition(&product, &[]), Player::Reach);
+        assert_eq!(winner_with_coalition(&product, &[1]), Player::Safe);
+    }
+
+    #[test]
+    fn recurrence_is_supported() {
+        // The violation is that a or b eventually never holds again, which also requires guessing
+        let product = LtlProduct::new(&choice_model(), "G F a & G F b");
+        assert_eq!(winner_with_coalition(&product, &[1]), Player::Reach);
+
+        let product = LtlProduct::new(&choice_model(), "G F a");
+        assert_eq!(winner_with_coalition(&product, &[]), Player::Reach);
+        assert_eq!(winner_with_coalition(&product, &[1]), Player::Safe);
+    }
+
+    #[test]
+    #[should_panic(expected = "are not supported")]
+    fn persistence_is_rejected() {
+        LtlProduct::new(&choice_model(), "F G a");
+    }
+
+    #[test]
+    fn counterexample_is_a_violating_lasso() {
+        let product = LtlProduct::new(&choice_model(), "G a");
+        let counterexample = product.find_counterexample().unwrap();
+        assert_eq!(counterexample, vec![0, 1, 2, 2]);
+        assert!(product.accepts(&counterexample));
+        assert!(!product.accepts(&[0, 1, 3, 3]));
+
+        let product = LtlProduct::new(&choice_model(), "G F a");
+        assert_eq!(product.find_counterexample(), Some(vec![0, 1, 2, 2]));
+        assert!(!product.accepts(&[0, 1, 3, 3]));
+        assert!(LtlProduct::new(&choice_model(), "F b")
+            .find_counterexample()
+            .is_none());
+    }
+}
@@ -1,21 +1,32 @@
+use crate::aggregation::AggregatedResponsibility;
 use crate::cli::{
     CounterexampleInput, Engine, ModelInput, ResponsibilityOutput, ResponsibilityVersion,
     Subcommand,
 };
-use crate::game::Game;
+use crate::game::{Game, Player, WitnessState};
+use crate::generator::RandomModel;
+use crate::ltl::LtlProduct;
 use crate::prism::transition_system_parser::TransitionSystemParser;
-use crate::shapley::{ResponsibilityCalculator, ResponsibilityResult, StateGroups, WeightType};
+use crate::self_check::EngineComparison;
+use crate::shapley::{
+    CoalitionStructure, ResponsibilityCalculator, ResponsibilityResult, StateGroups, WeightType,
+};
 use crate::transition_systems::TransitionSystem;
 use num_rational::BigRational;
-use num_traits::{One, Signed, ToPrimitive};
+use num_traits::{One, Signed, ToPrimitive, Zero};
 use std::fs::File;
 use std::io::Write;
 use std::path::PathBuf;
 
+mod aggregation;
+mod bdd;
 mod benchmarking;
 mod cli;
 mod game;
+mod generator;
+mod ltl;
 mod prism;
+mod self_check;
 mod shapley;
 mod transition_systems;
 
@@ -51,15 +62,23 @@ fn main() {
 
     match settings.subcommand {
         Subcommand::Run(run_command) => {
-            let bad_label = run_command.bad_label.expect(
-                "--bad-label must be set as bad-label auto-detection is not yet supported.",
-            );
+            // For liveness properties, the good label takes the place of the bad label when
+            // exporting and parsing the model. LTL formulas are checked on the product with an
+            // automaton, so PRISM only needs to export the model, for which a query for the
+            // built-in "init" label suffices.
+            let is_liveness = run_command.good_label.is_some();
+            let bad_label = match &run_command.ltl {
+                Some(_) => "init".to_string(),
+                None => run_command.bad_label.or(run_command.good_label).expect(
+                    "--bad-label or --good-label must be set as label auto-detection is not yet supported.",
+                ),
+            };
 
             let (model_input, counterexample_input) = run_command
                 .model_input
                 .apply_no_prism(settings.no_prism, run_command.counterexample_input);
 
-            let (ts, ce) = match model_input {
+            let (mut ts, ce) = match model_input {
                 ModelInput::PrismFile { file } => {
                     let prism_interface = prism::PrismInterface::new(prism_runner);
                     prism_interface.verify();
@@ -77,77 +96,310 @@ fn main() {
                 }
             };
 
-            let ce = match counterexample_input {
+            let good_states = is_liveness.then(|| ts.take_bad_states());
+            let ltl_product = run_command.ltl.as_ref().map(|formula| {
+                let product = LtlProduct::new(&ts, formula);
+                println!(
+                    "Built the product with the automaton for the negated formula, which has {} states.",
+                    product.node_count()
+                );
+                product
+            });
+
+            let is_aggregated = counterexample_input.is_aggregated();
+            let counterexamples = match counterexample_input {
+                CounterexampleInput::ModelChecker if ltl_product.is_some() => {
+                    let ce = ltl_product
+                        .as_ref()
+                        .unwrap()
+                        .find_counterexample()
+                        .expect("The LTL formula holds, so there is no counterexample.");
+
+                    print_counterexample(&ts, &ce);
+                    vec![("shortest lasso".to_string(), ce)]
+                }
+                CounterexampleInput::ModelChecker if is_liveness => {
+                    let ce = ts
+                        .lasso_counterexample(
+                            good_states.as_ref().unwrap(),
+                            run_command.infinitely_often,
+                        )
+                        .expect("The liveness property holds, so there is no counterexample.");
+
+                    print_counterexample(&ts, &ce);
+                    vec![("shortest lasso".to_string(), ce)]
+                }
                 CounterexampleInput::ModelChecker => {
                     let ce = ce.expect(
                         "Counterexample must be provided externally in this configuration.",
                     );
 
                     print_counterexample(&ts, &ce);
-                    ce
+                    vec![("PRISM counterexample".to_string(), ce)]
                 }
                 CounterexampleInput::File { file } => {
                     println!(
                         "Using counterexample from \"{}\" instead of PRISM output.",
                         file
                     );
-                    TransitionSystemParser::parse_counterexample_from_file(&file, &ts)
+                    let ce = TransitionSystemParser::parse_counterexample_from_file(&file, &ts);
+                    vec![(file, ce)]
+                }
+                CounterexampleInput::Files { files } => files
+                    .into_iter()
+                    .map(|file| {
+                        let ce = TransitionSystemParser::parse_counterexample_from_file(&file, &ts);
+                        (file, ce)
+                    })
+                    .collect(),
+                CounterexampleInput::AllShortest => {
+                    let mut paths = ts.shortest_counterexamples(MAX_SHORTEST_COUNTEREXAMPLES + 1);
+                    if paths.len() > MAX_SHORTEST_COUNTEREXAMPLES {
+                        println!(
+                            "There are more than {} shortest paths to a bad state, only the first {} are used.",
+                            MAX_SHORTEST_COUNTEREXAMPLES, MAX_SHORTEST_COUNTEREXAMPLES
+                        );
+                        paths.truncate(MAX_SHORTEST_COUNTEREXAMPLES);
+                    }
+                    paths
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, path)| (format!("shortest path {}", i + 1), path))
+                        .collect()
+                }
+                CounterexampleInput::Random { count } => {
+                    let seed = run_command.seed.unwrap_or_else(|| fastrand::u64(..));
+                    let mut rng = fastrand::Rng::with_seed(seed);
+                    let mut paths: Vec<Vec<usize>> = Vec::new();
+                    for _ in 0..count {
+                        let path = ts
+                            .random_counterexample(&mut rng)
+                            .expect("No bad state is reachable from the initial state.");
+                        if !paths.contains(&path) {
+                            paths.push(path);
+                        }
+                    }
+                    println!(
+                        "Drew {} random paths to a bad state (seed {}), {} of them distinct.",
+                        count,
+                        seed,
+                        paths.len()
+                    );
+                    paths
+                        .into_iter()
+                        .enumerate()
+                        .map(|(i, path)| (format!("random path {}", i + 1), path))
+                        .collect()
+                }
+                CounterexampleInput::Unused => {
+                    println!("Computing forward responsibility, which does not depend on a counterexample.");
+                    Vec::new()
                 }
             };
+            if counterexamples.is_empty()
+                && settings.responsibility_version != ResponsibilityVersion::Forward
+            {
+                panic!("No counterexample to compute responsibility for.");
+            }
+            for (_, ce) in &counterexamples {
+                match (&good_states, &ltl_product) {
+                    (Some(good_states), _) => {
+                        ts.verify_lasso(ce, good_states, run_command.infinitely_often)
+                    }
+                    (None, Some(ltl_product)) => {
+                        ts.verify_lasso(ce, &[], false);
+                        if !ltl_product.accepts(ce) {
+                            panic!("Counterexample is not a lasso that violates the LTL formula.");
+                        }
+                    }
+                    (None, None) => ts.verify_counterexample(ce),
+                }
+            }
+            if let Some(steps) = run_command.within {
+                for (name, ce) in &counterexamples {
+                    if ce.len() - 1 > steps {
+                        panic!(
+                            "Counterexample \"{}\" reaches a bad state after {} steps, but --within only allows {}.",
+                            name,
+                            ce.len() - 1,
+                            steps
+                        );
+                    }
+                }
+            }
 
-            ts.verify_counterexample(&ce);
-
-            let mut game = Game::from_transition_system(&ts);
-            game.mark_counterexample_path(ce);
+            // Responsibility is only compared if shortening changes the counterexample
+            let shortened_ce = if run_command.shorten {
+                if is_aggregated || counterexamples.is_empty() {
+                    panic!("--shorten requires a single counterexample.");
+                }
+                let ce = &counterexamples[0].1;
+                let shortened = ts.shorten_counterexample(ce);
+                if shortened == *ce {
+                    println!(
+                        "The counterexample is already a shortest path to its first bad state."
+                    );
+                    None
+                } else {
+                    println!(
+                        "Shortened the counterexample from {} to {} steps.",
+                        ce.len() - 1,
+                        shortened.len() - 1
+                    );
+                    print_counterexample(&ts, &shortened);
+                    Some(shortened)
+                }
+            } else {
+                None
+            };
 
             let thread_count = match settings.thread_count {
                 Some(thread_count) => thread_count,
                 None => rayon::current_num_threads(),
             };
 
-            let state_groups = if run_command.grouped {
-                println!("Grouping states by label.");
-                StateGroups::grouped_by_label_from_game(&game)
+            let custom_groups = if let Some(groups_file) = &run_command.groups_file {
+                let groups = TransitionSystemParser::parse_groups_from_file(groups_file, &ts);
+                println!("Read {} groups from \"{}\".", groups.len(), groups_file);
+                Some(groups)
+            } else if !run_command.group_variables.is_empty() {
+                let groups = ts.groups_by_variables(&run_command.group_variables);
+                println!(
+                    "Formed {} groups by the values of {}.",
+                    groups.len(),
+                    run_command.group_variables.join(", ")
+                );
+                Some(groups)
             } else {
-                match settings.responsibility_version {
-                    ResponsibilityVersion::Optimistic => StateGroups::individual_on_path(&game),
-                    ResponsibilityVersion::Pessimistic => StateGroups::individual_from_game(&game),
-                }
+                None
             };
 
-            let mut responsibility_calculator = ResponsibilityCalculator::new(
-                game,
+            // Custom groups replace the labels, so they are used for grouping unless they define
+            // the unions of the Owen value
+            let grouped = run_command.grouped || custom_groups.is_some() && !run_command.owen;
+            if grouped {
+                println!("Grouping states by label.");
+            }
+
+            let responsibility_run = ResponsibilityRun {
+                ts: &ts,
+                engine: &run_command.engine,
+                seed: run_command.seed,
+                prune: run_command.prune,
+                exclude_dummies: run_command.exclude_dummies,
+                symmetry: run_command.symmetry,
+                owen: run_command.owen,
+                custom_groups,
+                good_states,
+                infinitely_often: run_command.infinitely_often,
+                ltl_product: ltl_product.as_ref(),
+                within: run_command.within,
+                fix: run_command.fix,
+                costs: &run_command.costs,
+                strategy_coalition: run_command.strategy_coalition.as_deref(),
+                grouped,
+                metric: &settings.responsibility_metric,
+                responsibility_version: settings.responsibility_version,
                 thread_count,
-                settings.responsibility_metric,
-                state_groups,
-                settings.responsibility_version,
-            );
-            let mut responsibilities = match run_command.engine {
-                Engine::Exact => responsibility_calculator.compute_individual_responsibility(),
-                Engine::Stochastic(target) => {
-                    println!("Using stochastic engine.");
-                    responsibility_calculator.sample_individual_responsibilities(target)
-                }
             };
-            responsibilities.sort_by(|x, y| y.total_value.cmp(&x.total_value));
-            match run_command.responsibility_output {
+
+            if let Some(runs) = run_command.check_runs {
+                if is_aggregated {
+                    panic!("--check requires a single counterexample.");
+                }
+                let ce = counterexamples.into_iter().next().map(|(_, ce)| ce);
+                check_stochastic_engine(&responsibility_run, ce, runs);
+                return;
+            }
+
+            if is_aggregated {
+                let counterexample_count = counterexamples.len();
+                println!(
+                    "Aggregating responsibility over {} counterexamples.",
+                    counterexample_count
+                );
+                let mut aggregated = AggregatedResponsibility::new();
+                for (i, (name, ce)) in counterexamples.into_iter().enumerate() {
+                    let computed = responsibility_run.compute(Some(ce), true);
+                    println!(
+                        "Computed responsibility for counterexample {} of {} ({}).",
+                        i + 1,
+                        counterexample_count,
+                        name
+                    );
+                    let values = computed
+                        .responsibilities
+                        .into_iter()
+                        .map(|r| {
+                            (
+                                computed.state_groups.get_name(r.group_index, &ts),
+                                r.total_value,
+                            )
+                        })
+                        .collect();
+                    aggregated.add_counterexample(name, values);
+                }
+                match &run_command.responsibility_output {
+                    ResponsibilityOutput::Stdout => print_aggregated_responsibility(
+                        &aggregated,
+                        &settings.responsibility_metric,
+                        &mut std::io::stdout(),
+                    ),
+                    ResponsibilityOutput::File { file } => {
+                        let mut output = File::create(file).unwrap_or_else(|e| {
+                            panic!("Could not create responsibility output file: {}", e)
+                        });
+                        print_aggregated_responsibility(
+                            &aggregated,
+                            &settings.responsibility_metric,
+                            &mut output,
+                        );
+                        println!("Stored responsibility values in \"{}\"", file);
+                    }
+                }
+                return;
+            }
+
+            let ce = counterexamples.into_iter().next().map(|(_, ce)| ce);
+            let computed = responsibility_run.compute(ce, false);
+            let responsibilities = computed.responsibilities;
+            match &run_command.responsibility_output {
                 ResponsibilityOutput::Stdout => {
                     print_responsibility(
                         &responsibilities,
                         &ts,
-                        settings.responsibility_metric,
-                        &responsibility_calculator.state_groups,
+                        &settings.responsibility_metric,
+                        &computed.state_groups,
                         run_command.engine.is_stochastic(),
                     );
                 }
                 ResponsibilityOutput::File { file } => store_responsibility(
                     &responsibilities,
                     &ts,
-                    settings.responsibility_metric,
-                    &responsibility_calculator.state_groups,
-                    file,
+                    &settings.responsibility_metric,
+                    &computed.state_groups,
+                    file.clone(),
                 ),
             };
+            if let Some(coalition_structure) = &computed.coalition_structure {
+                print_union_responsibility(&responsibilities, coalition_structure);
+            }
+            if let Some(strategy) = &computed.strategy {
+                print_strategy(strategy, &ts);
+            }
+            if let Some(cheapest_fixes) = &computed.cheapest_fixes {
+                print_cheapest_fixes(cheapest_fixes, &ts, &computed.state_groups);
+            }
+            if let Some(shortened_ce) = shortened_ce {
+                let shortened = responsibility_run.compute(Some(shortened_ce), true);
+                print_shortening_comparison(
+                    &responsibilities,
+                    &computed.state_groups,
+                    &shortened,
+                    &ts,
+                    &settings.responsibility_metric,
+                );
+            }
         }
         Subcommand::Benchmark(benchmark_command) => {
             let file = std::fs::read_to_string(&benchmark_command.file)
@@ -237,11 +489,373 @@ fn main() {
                 );
             }
             benchmarker.run();
-            return;
+        }
+        Subcommand::Generate(generate_command) => {
+            let model = RandomModel::generate(&generate_command.settings);
+            model.write(&generate_command.stem, &generate_command.bad_label);
+            println!(
+                "Generated a transition system with {} states, {} transitions and {} bad states (seed {}) and a counterexample with {} steps in \"{}.sta\", \"{}.tra\", \"{}.lab\" and \"{}.ce\".",
+                generate_command.settings.state_count,
+                model.transition_count(),
+                model.bad_count(),
+                generate_command.settings.seed,
+                generate_command.settings.counterexample_length,
+                generate_command.stem,
+                generate_command.stem,
+                generate_command.stem,
+                generate_command.stem
+            );
         }
     }
 }
 
+// Computing responsibility for more shortest paths than this is rarely feasible
+const MAX_SHORTEST_COUNTEREXAMPLES: usize = 1000;
+
+// Everything that is needed to compute responsibility for a counterexample of the model
+#[derive(Clone)]
+struct ResponsibilityRun<'a> {
+    ts: &'a TransitionSystem,
+    engine: &'a Engine,
+    seed: Option<u64>,
+    prune: bool,
+    exclude_dummies: bool,
+    symmetry: bool,
+    owen: bool,
+    custom_groups: Option<Vec<(String, Vec<usize>)>>,
+    good_states: Option<Vec<usize>>, // Set for liveness properties
+    infinitely_often: bool,
+    ltl_product: Option<&'a LtlProduct>,
+    within: Option<usize>,
+    fix: bool,
+    costs: &'a [(String, BigRational)],
+    strategy_coalition: Option<&'a [String]>,
+    grouped: bool,
+    metric: &'a WeightType,
+    responsibility_version: ResponsibilityVersion,
+    thread_count: usize,
+}
+
+struct ComputedResponsibility {
+    responsibilities: Vec<ResponsibilityResult>, // Sorted by decreasing responsibility
+    state_groups: StateGroups,
+    coalition_structure: Option<CoalitionStructure>,
+    // Set if fixes were requested, to None if no coalition wins
+    cheapest_fixes: Option<Option<(BigRational, Vec<Vec<usize>>)>>,
+    strategy: Option<CoalitionStrategy>, // Set if a strategy was requested
+    dummy_groups: Vec<usize>,            // Empty unless dummies were excluded
+}
+
+struct CoalitionStrategy {
+    coalition: Vec<String>, // The names of the state (groups)
+    outcome: CoalitionOutcome,
+}
+
+enum CoalitionOutcome {
+    Wins(Vec<(usize, usize)>), // The successor that each coalition state chooses
+    Loses(Vec<WitnessState>),  // How the other states force the play into a bad state
+}
+
+impl ResponsibilityRun<'_> {
+    // Forward responsibility is computed without a counterexample, all other versions need one
+    fn compute(&self, ce: Option<Vec<usize>>, silent: bool) -> ComputedResponsibility {
+        let mut game = match self.ltl_product {
+            Some(ltl_product) => ltl_product.game().clone(),
+            None => Game::from_transition_system(self.ts),
+        };
+        if let Some(good_states) = &self.good_states {
+            game.set_liveness_objective(good_states.clone(), self.infinitely_often);
+        }
+        match (ce, self.ltl_product) {
+            (Some(ce), Some(ltl_product)) => ltl_product.mark_counterexample(&mut game, &ce),
+            (Some(ce), None) => game.mark_counterexample_path(ce),
+            (None, _) => {}
+        }
+        if let Some(groups) = &self.custom_groups {
+            match self.ltl_product {
+                Some(ltl_product) => game.replace_labels(ltl_product.lift_groups(groups)),
+                None => game.replace_labels(groups.clone()),
+            }
+        }
+        let mut unrolled_copies = None;
+        if let Some(steps) = self.within {
+            let (unrolled, copies) = game.unroll(steps);
+            game = unrolled;
+            unrolled_copies = Some(copies);
+        }
+        // In a product or an unrolled game, every state of the transition system has several
+        // copies, which are grouped together
+        let copies = unrolled_copies
+            .as_deref()
+            .or(self.ltl_product.map(LtlProduct::model_nodes));
+
+        let build_state_groups = |game: &Game| {
+            if self.grouped {
+                StateGroups::grouped_by_label_from_game(game)
+            } else if let Some(copies) = copies {
+                let only_on_path = self.responsibility_version == ResponsibilityVersion::Optimistic;
+                StateGroups::by_original_state(game, self.ts, copies, only_on_path)
+            } else {
+                match self.responsibility_version {
+                    ResponsibilityVersion::Optimistic => StateGroups::individual_on_path(game),
+                    ResponsibilityVersion::Pessimistic | ResponsibilityVersion::Forward => {
+                        StateGroups::individual_from_game(game)
+                    }
+                }
+            }
+        };
+
+        // The strategy is computed before pruning, which would redirect transitions to the sink
+        let strategy = self.strategy_coalition.map(|names| {
+            if self.responsibility_version == ResponsibilityVersion::Optimistic {
+                panic!("Strategies are only supported for pessimistic or forward responsibility.");
+            }
+            let state_groups = build_state_groups(&game);
+            let mut coalition_game = game.clone();
+            let mut coalition = Vec::new();
+            for name in names {
+                let group = find_group(&state_groups, self.ts, name).unwrap_or_else(|| {
+                    panic!(
+                        "\"{}\" in --strategy does not match any state (group).",
+                        name
+                    )
+                });
+                state_groups.add_to_coalition(&mut coalition_game, group);
+                coalition.push(state_groups.get_name(group, self.ts));
+            }
+            let outcome = match coalition_game.safe_strategy() {
+                Some(transitions) => CoalitionOutcome::Wins(transitions),
+                None => CoalitionOutcome::Loses(coalition_game.reach_witness().unwrap()),
+            };
+            CoalitionStrategy { coalition, outcome }
+        });
+
+        if self.prune {
+            let players_before = build_state_groups(&game).len();
+            let summary = game.prune();
+            let players_after = build_state_groups(&game).len();
+            if !silent {
+                println!(
+                    "Pruned {} unreachable states and collapsed {} states that cannot reach a bad state into a sink, eliminating {} of {} players.",
+                    summary.unreachable_states,
+                    summary.collapsed_states,
+                    players_before - players_after,
+                    players_before
+                );
+            }
+        }
+        let state_groups = build_state_groups(&game);
+        let coalition_structure = self
+            .owen
+            .then(|| CoalitionStructure::by_labels(&game, &state_groups));
+
+        let mut responsibility_calculator = ResponsibilityCalculator::new(
+            game,
+            self.thread_count,
+            self.metric.clone(),
+            state_groups,
+            self.responsibility_version,
+        );
+        responsibility_calculator.set_silent(silent);
+        if let Some(seed) = self.seed {
+            responsibility_calculator.set_seed(seed);
+        }
+        let mut dummy_groups = Vec::new();
+        if self.exclude_dummies {
+            let group_count = responsibility_calculator.state_groups.len();
+            dummy_groups = responsibility_calculator.exclude_dummy_groups();
+            if !silent {
+                println!(
+                    "Excluded {} of {} state (groups) as dummies. They have responsibility zero.",
+                    dummy_groups.len(),
+                    group_count
+                );
+            }
+        }
+        if self.symmetry {
+            let start_time = std::time::Instant::now();
+            let classes = responsibility_calculator.detect_symmetries();
+            if !silent {
+                println!(
+                    "Found {} classes of symmetric state (groups) in {:.2?}.",
+                    classes.len(),
+                    start_time.elapsed()
+                );
+                for class in classes {
+                    let names = class
+                        .iter()
+                        .map(|&group| {
+                            responsibility_calculator
+                                .state_groups
+                                .get_name(group, self.ts)
+                        })
+                        .collect::<Vec<_>>();
+                    println!("  {{{}}}", names.join(", "));
+                }
+            }
+        }
+        let cheapest_fixes = self.fix.then(|| {
+            let costs = self.group_costs(&responsibility_calculator.state_groups, silent);
+            responsibility_calculator.cheapest_fixes(&costs)
+        });
+        let mut responsibilities = match (&coalition_structure, self.engine) {
+            (Some(coalition_structure), Engine::Exact) => {
+                if !silent {
+                    println!(
+                        "Computing the Owen value for {} unions of states with the same labels.",
+                        coalition_structure.unions().len()
+                    );
+                }
+                responsibility_calculator.compute_owen_values(coalition_structure)
+            }
+            (Some(_), _) => panic!("The Owen value is only supported by the exact engine."),
+            (None, Engine::Exact) => responsibility_calculator.compute_individual_responsibility(),
+            (None, Engine::Antichain) => {
+                if !silent {
+                    println!("Using antichain engine.");
+                }
+                responsibility_calculator.compute_responsibility_from_antichain()
+            }
+            (None, Engine::Symbolic) => {
+                if !silent {
+                    println!("Using symbolic engine.");
+                }
+                responsibility_calculator.compute_responsibility_symbolically()
+            }
+            (None, &Engine::Stochastic(target, estimator)) => {
+                if !silent {
+                    println!(
+                        "Using stochastic engine ({} estimator, seed {}).",
+                        estimator,
+                        responsibility_calculator.seed()
+                    );
+                }
+                responsibility_calculator.sample_individual_responsibilities(target, estimator)
+            }
+        };
+        responsibilities.sort_by(|x, y| y.total_value.cmp(&x.total_value));
+
+        ComputedResponsibility {
+            responsibilities,
+            state_groups: responsibility_calculator.state_groups,
+            coalition_structure,
+            cheapest_fixes,
+            strategy,
+            dummy_groups,
+        }
+    }
+
+    // Returns the name and responsibility of every state (group)
+    fn named_values(&self, computed: &ComputedResponsibility) -> Vec<(String, BigRational)> {
+        computed
+            .responsibilities
+            .iter()
+            .map(|r| {
+                (
+                    computed.state_groups.get_name(r.group_index, self.ts),
+                    r.total_value.clone(),
+                )
+            })
+            .collect()
+    }
+
+    // Returns the cost of every state group. Groups without a cost cost 1.
+    fn group_costs(&self, state_groups: &StateGroups, silent: bool) -> Vec<BigRational> {
+        let mut costs = vec![BigRational::one(); state_groups.len()];
+        for (name, cost) in self.costs {
+            match find_group(state_groups, self.ts, name) {
+                Some(group) => costs[group] = cost.clone(),
+                None if !silent => {
+                    println!("Cost for \"{}\" does not match any state (group).", name)
+                }
+                None => {}
+            }
+        }
+        costs
+    }
+}
+
+// Computes the exact values, runs the stochastic engine of the run several times with consecutive
+// seeds and compares the estimates with the exact values
+fn check_stochastic_engine(run: &ResponsibilityRun, ce: Option<Vec<usize>>, runs: usize) {
+    let seed = run.seed.unwrap_or_else(|| fastrand::u64(..));
+    let (comparison, dummy_names) = compare_with_exact_engine(run, ce, runs, seed);
+    print_engine_comparison(&comparison, &dummy_names, runs, run.metric);
+}
+
+// Returns the comparison and the names of the dummy state (groups)
+fn compare_with_exact_engine(
+    run: &ResponsibilityRun,
+    ce: Option<Vec<usize>>,
+    runs: usize,
+    seed: u64,
+) -> (EngineComparison, Vec<String>) {
+    // The symbolic engine supports the same metrics as the stochastic engine and is not limited to
+    // 64 state (groups), but it only supports reachability objectives. Dummies are excluded to
+    // check that both engines give them responsibility zero.
+    let is_reachability = run.good_states.is_none() && run.ltl_product.is_none();
+    let (engine, engine_name) = if is_reachability {
+        (&Engine::Symbolic, "symbolic")
+    } else {
+        (&Engine::Exact, "exact")
+    };
+    println!(
+        "Checking the stochastic engine against the {} engine with {} runs (seeds {} to {}).",
+        engine_name,
+        runs,
+        seed,
+        seed.wrapping_add(runs as u64 - 1)
+    );
+    let exact_run = ResponsibilityRun {
+        engine,
+        exclude_dummies: true,
+        ..run.clone()
+    };
+    let exact = exact_run.compute(ce.clone(), true);
+    println!(
+        "Computed exact responsibility with the {} engine.",
+        engine_name
+    );
+    let dummy_names = exact
+        .dummy_groups
+        .iter()
+        .map(|&group| exact.state_groups.get_name(group, run.ts))
+        .collect::<Vec<_>>();
+
+    let mut comparison = EngineComparison::new(run.named_values(&exact));
+    for i in 0..runs {
+        let sampled_run = ResponsibilityRun {
+            seed: Some(seed.wrapping_add(i as u64)),
+            ..run.clone()
+        };
+        let sampled = sampled_run.compute(ce.clone(), true);
+        println!(
+            "Finished run {} of {} of the stochastic engine.",
+            i + 1,
+            runs
+        );
+        comparison.add_run(run.named_values(&sampled));
+    }
+    (comparison, dummy_names)
+}
+
+// Returns the index of the state group with the given name as in the responsibility output, where
+// whitespace and the parentheses around states are optional
+fn find_group(state_groups: &StateGroups, ts: &TransitionSystem, name: &str) -> Option<usize> {
+    let normalise = |name: &str| {
+        let name = name.split_whitespace().collect::<String>();
+        match name
+            .strip_prefix('(')
+            .and_then(|name| name.strip_suffix(')'))
+        {
+            Some(name) => name.to_string(),
+            None => name,
+        }
+    };
+    let name = normalise(name);
+    (0..state_groups.len()).find(|&group| normalise(&state_groups.get_name(group, ts)) == name)
+}
+
 fn print_counterexample(transition_system: &TransitionSystem, counterexample: &Vec<usize>) {
     println!("\nCounterexample:");
     for &state_index in counterexample {
@@ -254,7 +868,7 @@ fn print_counterexample(transition_system: &TransitionSystem, counterexample: &V
 fn print_responsibility(
     responsibilities: &Vec<ResponsibilityResult>,
     transition_system: &TransitionSystem,
-    metric: WeightType,
+    metric: &WeightType,
     state_groups: &StateGroups,
     is_stochastic: bool,
 ) {
@@ -273,8 +887,9 @@ fn print_responsibility(
 
     if let Some(sum_f64) = sum.to_f64() {
         println!("Sum of responsibilities: {}", sum_f64);
-        if !is_stochastic && metric == WeightType::Shapley && !sum.is_one() {
-            println!("  Sum is not 1, but it should be for Shapley weights.");
+        let is_normalised = *metric == WeightType::Shapley || metric.is_power_index();
+        if !is_stochastic && is_normalised && !sum.is_one() {
+            println!("  Sum is not 1, but it should be for {} weights.", metric);
             if (sum_f64 - 1.0).abs() < 0.00001 {
                 println!("  Exact value: {}", sum);
             }
@@ -282,10 +897,296 @@ fn print_responsibility(
     }
 }
 
+fn print_cheapest_fixes(
+    cheapest_fixes: &Option<(BigRational, Vec<Vec<usize>>)>,
+    transition_system: &TransitionSystem,
+    state_groups: &StateGroups,
+) {
+    match cheapest_fixes {
+        Some((cost, coalitions)) => {
+            println!(
+                "\nCheapest fixes, i.e. winning coalitions of minimum cost ({}):",
+                cost
+            );
+            for coalition in coalitions {
+                let names = coalition
+                    .iter()
+                    .map(|&group| state_groups.get_name(group, transition_system))
+                    .collect::<Vec<_>>();
+                println!("  {{{}}}", names.join(", "));
+            }
+        }
+        None => println!("\nEven the coalition of all state (groups) loses, so there is no fix."),
+    }
+}
+
+fn print_strategy(strategy: &CoalitionStrategy, transition_system: &TransitionSystem) {
+    let coalition = strategy.coalition.join(", ");
+    match &strategy.outcome {
+        CoalitionOutcome::Wins(transitions) => {
+            println!("\nWinning strategy of the coalition {{{}}}:", coalition);
+            for &(state, successor) in transitions {
+                println!(
+                    "  ({}) -> {}",
+                    transition_system.states[state].to_string(&transition_system.variables),
+                    format_successor(transition_system, state, successor)
+                );
+            }
+        }
+        CoalitionOutcome::Loses(witness) => {
+            println!(
+                "\nThe coalition {{{}}} loses. The other states force the play into a bad state as follows, where the rank bounds the number of steps until then:",
+                coalition
+            );
+            for witness_state in witness {
+                let owner = match witness_state.owner {
+                    Player::Safe => ", coalition",
+                    Player::Path => ", counterexample",
+                    Player::Reach => "",
+                };
+                let successors = if witness_state.rank == 0 {
+                    " bad".to_string()
+                } else {
+                    let successors = witness_state
+                        .successors
+                        .iter()
+                        .map(|&successor| {
+                            format_successor(transition_system, witness_state.state, successor)
+                        })
+                        .collect::<Vec<_>>();
+                    format!(" -> {}", successors.join(", "))
+                };
+                println!(
+                    "  ({}) [rank {}{}]{}",
+                    transition_system.states[witness_state.state]
+                        .to_string(&transition_system.variables),
+                    witness_state.rank,
+                    owner,
+                    successors
+                );
+            }
+        }
+    }
+}
+
+// Formats the successor of a state together with the names of the actions that lead to it
+fn format_successor(
+    transition_system: &TransitionSystem,
+    state: usize,
+    successor: usize,
+) -> String {
+    let actions = transition_system.states[state]
+        .outgoing_transitions
+        .iter()
+        .filter(|transition| transition.destination == successor)
+        .filter_map(|transition| transition.action.clone())
+        .collect::<Vec<_>>();
+    let successor = transition_system.states[successor].to_string(&transition_system.variables);
+    if actions.is_empty() {
+        format!("({})", successor)
+    } else {
+        format!("({}) [{}]", successor, actions.join(", "))
+    }
+}
+
+fn print_union_responsibility(
+    responsibilities: &[ResponsibilityResult],
+    coalition_structure: &CoalitionStructure,
+) {
+    println!("\nResponsibilities of unions:");
+    let values = coalition_structure.union_values(responsibilities);
+    let mut unions = coalition_structure
+        .unions()
+        .iter()
+        .zip(values)
+        .collect::<Vec<_>>();
+    unions.sort_by(|(_, x), (_, y)| y.cmp(x));
+    for (union, value) in unions {
+        match value.to_f64() {
+            Some(value_f64) => println!("({}): {:.8}", union.name, value_f64),
+            None => println!("({}): {}", union.name, value),
+        }
+    }
+}
+
+// Prints the responsibility of every state (group) for the original and the shortened
+// counterexample. Groups are matched by name, as the significant states can differ between both.
+fn print_shortening_comparison(
+    original: &[ResponsibilityResult],
+    original_groups: &StateGroups,
+    shortened: &ComputedResponsibility,
+    transition_system: &TransitionSystem,
+    metric: &WeightType,
+) {
+    let mut values: Vec<(String, BigRational, BigRational)> = original
+        .iter()
+        .map(|r| {
+            (
+                original_groups.get_name(r.group_index, transition_system),
+                r.total_value.clone(),
+                BigRational::zero(),
+            )
+        })
+        .collect();
+    for r in &shortened.responsibilities {
+        let name = shortened
+            .state_groups
+            .get_name(r.group_index, transition_system);
+        match values.iter_mut().find(|(existing, _, _)| *existing == name) {
+            Some((_, _, value)) => *value = r.total_value.clone(),
+            None => values.push((name, BigRational::zero(), r.total_value.clone())),
+        }
+    }
+
+    println!(
+        "\nResponsibilities ({}) for the original and the shortened counterexample:",
+        metric
+    );
+    let format_value = |value: &BigRational| match value.to_f64() {
+        Some(value_f64) => format!("{:.8}", value_f64),
+        None => value.to_string(),
+    };
+    for (name, original_value, shortened_value) in &values {
+        if original_value.is_zero() && shortened_value.is_zero() {
+            continue;
+        }
+        let difference = shortened_value - original_value;
+        let change = if difference.is_zero() {
+            "unchanged".to_string()
+        } else {
+            format!(
+                "{}{}",
+                if difference.is_positive() { "+" } else { "" },
+                format_value(&difference)
+            )
+        };
+        println!(
+            "({}): {} -> {} ({})",
+            name,
+            format_value(original_value),
+            format_value(shortened_value),
+            change
+        );
+    }
+}
+
+fn print_engine_comparison(
+    comparison: &EngineComparison,
+    dummy_names: &[String],
+    runs: usize,
+    metric: &WeightType,
+) {
+    println!(
+        "\nResponsibilities ({}) of the exact and the stochastic engine, with 95% confidence intervals over {} runs:",
+        metric, runs
+    );
+    let comparisons = comparison.comparisons();
+    for group in &comparisons {
+        if group.exact.is_zero() && group.always_zero {
+            continue;
+        }
+        let relative_error = match group.relative_error {
+            Some(relative_error) => format!("{:.2}%", relative_error * 100.0),
+            None => "undefined".to_string(),
+        };
+        println!(
+            "({}): exact {:.8}, sampled {:.8} ± {:.8}, absolute error {:.8}, relative error {}{}",
+            group.name,
+            group.exact.to_f64().unwrap_or(f64::NAN),
+            group.mean,
+            group.half_width,
+            group.absolute_error,
+            relative_error,
+            if group.in_interval {
+                ""
+            } else {
+                ", outside the interval"
+            }
+        );
+    }
+    let in_interval = comparisons.iter().filter(|group| group.in_interval).count();
+    println!(
+        "{} of {} exact values lie in the confidence interval of the stochastic engine.",
+        in_interval,
+        comparisons.len()
+    );
+
+    if *metric == WeightType::Shapley {
+        let exact_sum = comparison.exact_sum();
+        if exact_sum.is_one() {
+            println!(
+                "Efficiency holds: the exact responsibilities sum to 1, the estimates to {:.8} on average.",
+                comparison.mean_sampled_sum()
+            );
+        } else {
+            println!(
+                "Efficiency is violated unless even the coalition of all state (groups) loses: the exact responsibilities sum to {}.",
+                exact_sum
+            );
+        }
+    }
+
+    // Dummies and state (groups) with exact responsibility zero are never critical
+    let violations = comparisons
+        .iter()
+        .filter(|group| {
+            let is_null_player =
+                group.exact.is_zero() || dummy_names.iter().any(|n| n == group.name);
+            is_null_player && !(group.exact.is_zero() && group.always_zero)
+        })
+        .collect::<Vec<_>>();
+    if violations.is_empty() {
+        println!(
+            "Null player property holds: every state (group) with exact responsibility zero ({} of them dummies) has responsibility zero in every run.",
+            dummy_names.len()
+        );
+    } else {
+        println!("Null player property is violated for:");
+        for group in violations {
+            println!(
+                "  ({}): exact {}, sampled {:.8} on average",
+                group.name, group.exact, group.mean
+            );
+        }
+    }
+}
+
+fn print_aggregated_responsibility(
+    aggregated: &AggregatedResponsibility,
+    metric: &WeightType,
+    output: &mut dyn Write,
+) {
+    let write_error = |e| panic!("Could not write responsibility: {}", e);
+    writeln!(
+        output,
+        "\nResponsibilities ({}) over {} counterexamples (mean, minimum and maximum):",
+        metric,
+        aggregated.counterexample_count()
+    )
+    .unwrap_or_else(write_error);
+    let format_value = |value: &BigRational| match value.to_f64() {
+        Some(value_f64) => format!("{:.8}", value_f64),
+        None => value.to_string(),
+    };
+    for summary in aggregated.summaries() {
+        writeln!(
+            output,
+            "({}): {}, min {} ({}), max {} ({})",
+            summary.name,
+            format_value(&summary.mean),
+            format_value(&summary.min),
+            summary.min_counterexample,
+            format_value(&summary.max),
+            summary.max_counterexample
+        )
+        .unwrap_or_else(write_error);
+    }
+}
+
 fn store_responsibility(
     responsibilities: &Vec<ResponsibilityResult>,
     transition_system: &TransitionSystem,
-    metric: WeightType,
+    metric: &WeightType,
     state_groups: &StateGroups,
     file_name: String,
 ) {
@@ -304,3 +1205,56 @@ fn store_responsibility(
     }
     println!("Stored responsibility values in \"{}\"", file_name);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::shapley::{Estimator, SampleTarget};
+
+    #[test]
+    fn check_supports_liveness_properties() {
+        // The good state 4 is only reached if both 0 and 2 move towards it, while the
+        // counterexample 0, 1, 3 ends in the sink 3. State 1 is a dummy.
+        let successors = vec![vec![1, 2], vec![1, 3], vec![3, 4], vec![3], vec![4]];
+        let ts = TransitionSystem::from_successors(&successors, &[]);
+        let run = ResponsibilityRun {
+            ts: &ts,
+            engine: &Engine::Stochastic(SampleTarget::Samples(100), Estimator::Coalition),
+            seed: None,
+            prune: false,
+            exclude_dummies: false,
+            symmetry: false,
+            owen: false,
+            custom_groups: None,
+            good_states: Some(vec![4]),
+            infinitely_often: false,
+            ltl_product: None,
+            within: None,
+            fix: false,
+            costs: &[],
+            strategy_coalition: None,
+            grouped: false,
+            metric: &WeightType::Shapley,
+            responsibility_version: ResponsibilityVersion::Pessimistic,
+            thread_count: 1,
+        };
+
+        let (comparison, dummy_names) = compare_with_exact_engine(&run, Some(vec![0, 1, 3]), 2, 1);
+        let mut exact = comparison
+            .comparisons()
+            .into_iter()
+            .map(|group| (group.name.to_string(), group.exact))
+            .collect::<Vec<_>>();
+        exact.sort();
+        let half = BigRational::new(1.into(), 2.into());
+        assert_eq!(
+            exact,
+            vec![
+                ("s=0".to_string(), half.clone()),
+                ("s=1".to_string(), BigRational::zero()),
+                ("s=2".to_string(), half),
+            ]
+        );
+        assert_eq!(dummy_names, vec!["s=1".to_string()]);
+    }
+}
@@ -89,6 +89,7 @@ impl PrismInterface {
 
     fn parse_counterexample_from_output(&self, output: String) -> Vec<String> {
         let mut counterexample = Vec::new();
+        #[allow(clippy::enum_variant_names)]
         enum ParserState {
             BeforeCounterexample,
             InCounterexample,
@@ -121,20 +122,12 @@ struct RunOutput {
     results_file_name_stem: String,
 }
 
+#[derive(Default)]
 pub struct RunResults {
     pub counterexample: Vec<usize>,
     pub transition_system: TransitionSystem,
 }
 
-impl Default for RunResults {
-    fn default() -> Self {
-        Self {
-            counterexample: Vec::new(),
-            transition_system: TransitionSystem::default(),
-        }
-    }
-}
-
 impl RunResults {
     pub fn new(counterexample: Vec<usize>, transition_system: TransitionSystem) -> Self {
         Self {
@@ -90,7 +90,7 @@ impl TransitionSystemParser {
         &valuations[1..valuations.len() - 1]
     }
 
-    fn parse_state_valuation_string(vars: &mut Vec<Variable>, valuations: &str) -> Vec<usize> {
+    fn parse_state_valuation_string(vars: &mut [Variable], valuations: &str) -> Vec<usize> {
         let mut valuation_indices = Vec::new();
         for (i, valuation) in valuations.split(",").enumerate() {
             valuation_indices.push(vars[i].get_valuation_index_or_add(valuation));
@@ -105,14 +105,14 @@ impl TransitionSystemParser {
         valuation_indices
     }
 
-    fn parse_transitions(transitions_file: String, states: &mut Vec<State>) {
+    fn parse_transitions(transitions_file: String, states: &mut [State]) {
         let mut lines = transitions_file.lines();
 
         // Currently, we just skip the header without parsing it
         lines.next().expect("Transition file is missing header");
 
         for transition_line in lines {
-            let (source, destination) = Self::parse_source_and_destination(transition_line);
+            let (source, destination, action) = Self::parse_transition(transition_line);
 
             if source >= states.len() {
                 panic!(
@@ -131,11 +131,12 @@ impl TransitionSystemParser {
 
             states[source]
                 .outgoing_transitions
-                .push(Transition::new(destination));
+                .push(Transition::new(destination, action));
         }
     }
 
-    fn parse_source_and_destination(transition_line: &str) -> (usize, usize) {
+    // Returns the source, the destination and the name of the action, if there is one
+    fn parse_transition(transition_line: &str) -> (usize, usize, Option<String>) {
         let mut components = transition_line.split(" ");
         let error_message = "Transition file entry is missing field: At least 4 entries (source, destination, action, probability) are required";
         let source: usize = components
@@ -161,12 +162,16 @@ impl TransitionSystemParser {
         if probability != 1.0 {
             panic!("Encountered transition with probability {}, but only transitions with probability 1 are supported.", probability);
         }
-        (source, destination)
+        let action = components
+            .next()
+            .filter(|action| !action.is_empty())
+            .map(|action| action.to_string());
+        (source, destination, action)
     }
 
     fn parse_labels(
         label_file: String,
-        states: &mut Vec<State>,
+        states: &mut [State],
         bad_label: &str,
     ) -> (usize, Vec<(usize, String)>) {
         let init_label_name = "init";
@@ -242,90 +247,202 @@ impl TransitionSystemParser {
         ce_strings: Vec<String>,
         ts: &TransitionSystem,
     ) -> Vec<usize> {
-        let mut ce = Vec::new();
-
-        for ce_string in &ce_strings {
-            let ce_string = &ce_string.trim()[1..ce_string.len() - 1];
-            let mut val_indices = vec![None; ts.variables.len()];
-
-            for assignment in ce_string.split(",") {
-                let (name, value) = assignment.split_once("=").unwrap_or_else(|| {
-                    panic!(
-                        "Variable assignment \"{}\" has incorrect format, as it is missing \"=\".",
-                        assignment
-                    )
-                });
-                let name = name.trim();
-                let value = value.trim();
-                let var_index = ts
-                    .get_variable_index(name)
-                    .unwrap_or_else(|| panic!("Could not find variable \"{}\"", name));
-                let val_index = ts.variables[var_index]
-                    .get_valuation_index(value)
-                    .unwrap_or_else(|| {
-                        panic!(
-                            "Could not find valuation \"{}\" for variable \"{}\".",
-                            value, name
-                        )
-                    });
-                if val_indices[var_index].is_some() {
-                    panic!(
-                        "Valuation contains two values for variable \"{}\".",
-                        ts.variables[var_index].name
-                    );
-                }
-                val_indices[var_index] = Some(val_index);
+        ce_strings
+            .iter()
+            .map(|ce_string| {
+                Self::parse_state_with_varnames(&ce_string.trim()[1..ce_string.len() - 1], ts)
+            })
+            .collect()
+    }
+
+    // Finds the state with the given valuation, e.g. "x=1, y=2"
+    fn parse_state_with_varnames(valuation: &str, ts: &TransitionSystem) -> usize {
+        let mut val_indices = vec![None; ts.variables.len()];
+
+        for assignment in valuation.split(",") {
+            let (name, value) = assignment.split_once("=").unwrap_or_else(|| {
+                panic!(
+                    "Variable assignment \"{}\" has incorrect format, as it is missing \"=\".",
+                    assignment
+                )
+            });
+            let (var_index, val_index) = Self::parse_assignment(name, value, ts);
+            if val_indices[var_index].is_some() {
+                panic!(
+                    "Valuation contains two values for variable \"{}\".",
+                    ts.variables[var_index].name
+                );
             }
+            val_indices[var_index] = Some(val_index);
+        }
 
-            let valuation_indices = val_indices
-                .into_iter()
-                .enumerate()
-                .map(|(i, v)| {
-                    v.unwrap_or_else(|| {
-                        panic!("Variable {} was not assigned a value", ts.variables[i].name)
-                    })
+        let valuation_indices = val_indices
+            .into_iter()
+            .enumerate()
+            .map(|(i, v)| {
+                v.unwrap_or_else(|| {
+                    panic!("Variable {} was not assigned a value", ts.variables[i].name)
                 })
-                .collect::<Vec<_>>();
+            })
+            .collect::<Vec<_>>();
+
+        ts.find_state_with_valuation(valuation_indices)
+            .unwrap_or_else(|| panic!("Could not find valuation ({}).", valuation))
+    }
 
-            ce.push(
-                ts.find_state_with_valuation(valuation_indices)
-                    .unwrap_or_else(|| panic!("Could not find valuation ({}).", ce_string)),
+    // Returns the index of the variable and of the value in an assignment "name=value"
+    fn parse_assignment(name: &str, value: &str, ts: &TransitionSystem) -> (usize, usize) {
+        let name = name.trim();
+        let value = value.trim();
+        let var_index = ts
+            .get_variable_index(name)
+            .unwrap_or_else(|| panic!("Could not find variable \"{}\"", name));
+        let val_index = ts.variables[var_index]
+            .get_valuation_index(value)
+            .unwrap_or_else(|| {
+                panic!(
+                    "Could not find valuation \"{}\" for variable \"{}\".",
+                    value, name
+                )
+            });
+        (var_index, val_index)
+    }
+
+    pub fn parse_counterexample(ce_strings: Vec<String>, ts: &TransitionSystem) -> Vec<usize> {
+        ce_strings
+            .iter()
+            .map(|ce_string| {
+                Self::parse_state(
+                    &ce_string.trim()[1..ce_string.len() - 1],
+                    ts,
+                    "the counterexample",
+                )
+            })
+            .collect()
+    }
+
+    // Finds the state with the given list of values, e.g. "1, 2". `source` is where the state was
+    // given, for the error message.
+    fn parse_state(valuation: &str, ts: &TransitionSystem, source: &str) -> usize {
+        let mut val_indices = Vec::new();
+        for (i, val) in valuation.split(",").enumerate() {
+            let val = val.trim();
+            let val_index = ts.variables[i].get_valuation_index(val);
+            val_indices.push(val_index.unwrap_or_else(|| {
+                panic!(
+                    "Unknown value {} for variable {}",
+                    val, ts.variables[i].name
+                )
+            }));
+        }
+        if val_indices.len() != ts.variables.len() {
+            panic!(
+                "A state in {} has an incorrect number of variable valuations ({} instead of {})",
+                source,
+                val_indices.len(),
+                ts.variables.len()
             );
         }
 
-        ce
+        let state_index = ts.find_state_with_valuation(val_indices);
+        state_index.expect("Transition system does not contain one of the specified states")
     }
 
-    pub fn parse_counterexample(ce_strings: Vec<String>, ts: &TransitionSystem) -> Vec<usize> {
-        let mut ce = Vec::new();
-
-        for ce_string in &ce_strings {
-            let ce_string = &ce_string.trim()[1..ce_string.len() - 1];
-            let mut val_indices = Vec::new();
-            for (i, val) in ce_string.split(",").enumerate() {
-                let val = val.trim();
-                let val_index = ts.variables[i].get_valuation_index(val);
-                val_indices.push(val_index.unwrap_or_else(|| {
-                    panic!(
-                        "Unknown value {} for variable {}",
-                        val, ts.variables[i].name
-                    )
-                }));
+    // Parses a file that defines one group of states per line. A line consists of the name of the
+    // group, a colon and either a list of states, e.g. "(x=1,y=2) (x=1,y=3)" or "(1,2) (1,3)", or a
+    // predicate over the variables, e.g. "sig1=red | sig2=red & sig3!=green". In predicates, "&"
+    // binds stronger than "|". Empty lines and lines starting with "//" are ignored.
+    pub fn parse_groups_from_file<P: AsRef<std::path::Path> + Display>(
+        file: P,
+        ts: &TransitionSystem,
+    ) -> Vec<(String, Vec<usize>)> {
+        let file = TransitionSystemParser::get_file_content(&file, "group file");
+        let mut groups = Vec::new();
+        for line in file.lines().map(|l| l.trim()) {
+            if line.is_empty() || line.starts_with("//") {
+                continue;
             }
-            if val_indices.len() != ts.variables.len() {
+            let (name, definition) = line.split_once(":").unwrap_or_else(|| {
                 panic!(
-                    "A state in the counterexample has an incorrect number of variable valuations ({} instead of {})",
-                    val_indices.len(),
-                    ts.variables.len()
-                );
+                    "Group definition \"{}\" has incorrect format, as it is missing \":\".",
+                    line
+                )
+            });
+            let name = name.trim().to_string();
+            let definition = definition.trim();
+            let mut states = if definition.starts_with("(") {
+                Self::parse_state_list(definition, ts)
+            } else {
+                Self::parse_predicate(definition, ts)
+            };
+            states.sort();
+            states.dedup();
+            if states.is_empty() {
+                println!("Group \"{}\" does not contain any states.", name);
             }
+            groups.push((name, states));
+        }
+        groups
+    }
 
-            let state_index = ts.find_state_with_valuation(val_indices);
-            ce.push(state_index.expect(
-                "Transition system does not contain one of the states contained in the counterexample",
-            ))
+    fn parse_state_list(definition: &str, ts: &TransitionSystem) -> Vec<usize> {
+        let mut states = Vec::new();
+        let mut rest = definition;
+        while let Some(start) = rest.find("(") {
+            let end = rest[start..]
+                .find(")")
+                .unwrap_or_else(|| panic!("State list \"{}\" is missing \")\".", definition))
+                + start;
+            let valuation = &rest[start + 1..end];
+            states.push(if valuation.contains("=") {
+                Self::parse_state_with_varnames(valuation, ts)
+            } else {
+                Self::parse_state(valuation, ts, "a group definition")
+            });
+            rest = &rest[end + 1..];
         }
+        states
+    }
 
-        ce
+    fn parse_predicate(definition: &str, ts: &TransitionSystem) -> Vec<usize> {
+        // A disjunction of conjunctions of (variable, value, is_negated)
+        let disjunction = definition
+            .split("|")
+            .map(|conjunction| {
+                conjunction
+                    .split("&")
+                    .map(|atom| {
+                        let (name, value, is_negated) = match atom.split_once("!=") {
+                            Some((name, value)) => (name, value, true),
+                            None => {
+                                let (name, value) = atom.split_once("=").unwrap_or_else(|| {
+                                    panic!(
+                                        "Predicate \"{}\" has incorrect format: \"{}\" is neither \"variable=value\" nor \"variable!=value\".",
+                                        definition,
+                                        atom.trim()
+                                    )
+                                });
+                                (name, value, false)
+                            }
+                        };
+                        let (var_index, val_index) = Self::parse_assignment(name, value, ts);
+                        (var_index, val_index, is_negated)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+
+        (0..ts.states.len())
+            .filter(|&state| {
+                let valuation = &ts.states[state].valuation_indices;
+                disjunction.iter().any(|conjunction| {
+                    conjunction
+                        .iter()
+                        .all(|&(var_index, val_index, is_negated)| {
+                            (valuation[var_index] == val_index) != is_negated
+                        })
+                })
+            })
+            .collect()
     }
 }
@@ -0,0 +1,105 @@
+use crate::aggregation::GroupTable;
+use num_rational::BigRational;
+use num_traits::{ToPrimitive, Zero};
+
+// Compares the exact responsibility of every state (group) with the estimates of several
+// independent runs of the stochastic engine. The first column of the table holds the exact values
+// and every run adds a column, so a group that is missing from the exact values has responsibility
+// zero. The confidence interval of a group is the 95% Student's t-interval of the mean over the
+// runs.
+pub struct EngineComparison {
+    table: GroupTable,
+}
+
+pub struct GroupComparison<'a> {
+    pub name: &'a str,
+    pub exact: BigRational,
+    pub mean: f64,
+    pub half_width: f64, // Half the width of the confidence interval around the mean
+    pub absolute_error: f64,
+    pub relative_error: Option<f64>, // None if the exact value is zero
+    pub in_interval: bool,
+    pub always_zero: bool, // Whether every run estimated exactly zero
+}
+
+impl EngineComparison {
+    pub fn new(exact_values: Vec<(String, BigRational)>) -> Self {
+        let mut table = GroupTable::new();
+        table.add_column(exact_values);
+        Self { table }
+    }
+
+    pub fn add_run(&mut self, values: Vec<(String, BigRational)>) {
+        self.table.add_column(values);
+    }
+
+    fn run_count(&self) -> usize {
+        self.table.column_count() - 1
+    }
+
+    pub fn exact_sum(&self) -> BigRational {
+        self.table.rows().map(|(_, values)| &values[0]).sum()
+    }
+
+    // Returns the mean of the sums of the estimates of all runs
+    pub fn mean_sampled_sum(&self) -> f64 {
+        let total = self
+            .table
+            .rows()
+            .flat_map(|(_, values)| &values[1..])
+            .sum::<BigRational>();
+        to_f64(&total) / self.run_count() as f64
+    }
+
+    // Returns the comparison of every group, sorted by decreasing exact responsibility
+    pub fn comparisons(&self) -> Vec<GroupComparison<'_>> {
+        let runs = self.run_count() as f64;
+        let t = t_quantile_975(self.run_count() - 1);
+        let mut comparisons = self
+            .table
+            .rows()
+            .map(|(name, values)| {
+                let exact = &values[0];
+                let values = values[1..].iter().map(to_f64).collect::<Vec<_>>();
+                let mean = values.iter().sum::<f64>() / runs;
+                let variance =
+                    values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / (runs - 1.0);
+                let half_width = t * (variance / runs).sqrt();
+                let exact_f64 = to_f64(exact);
+                let absolute_error = (mean - exact_f64).abs();
+                GroupComparison {
+                    name,
+                    exact: exact.clone(),
+                    mean,
+                    half_width,
+                    absolute_error,
+                    relative_error: (!exact.is_zero()).then(|| absolute_error / exact_f64.abs()),
+                    // Allow for the rounding of the estimates when the interval is degenerate
+                    in_interval: absolute_error <= half_width + 1e-9,
+                    always_zero: values.iter().all(|v| *v == 0.0),
+                }
+            })
+            .collect::<Vec<_>>();
+        comparisons.sort_by(|x, y| y.exact.cmp(&x.exact));
+        comparisons
+    }
+}
+
+fn to_f64(value: &BigRational) -> f64 {
+    value.to_f64().unwrap_or(f64::NAN)
+}
+
+// The 97.5% quantile of Student's t-distribution with the given degrees of freedom, which bounds
+// a two-sided 95% confidence interval. Beyond the table, the normal quantile is close enough.
+fn t_quantile_975(degrees_of_freedom: usize) -> f64 {
+    const QUANTILES: [f64; 30] = [
+        12.706, 4.303, 3.182, 2.776, 2.571, 2.447, 2.365, 2.306, 2.262, 2.228, 2.201, 2.179, 2.160,
+        2.145, 2.131, 2.120, 2.110, 2.101, 2.093, 2.086, 2.080, 2.074, 2.069, 2.064, 2.060, 2.056,
+        2.052, 2.048, 2.045, 2.042,
+    ];
+    match degrees_of_freedom {
+        0 => panic!("A confidence interval requires at least two runs."),
+        1..=30 => QUANTILES[degrees_of_freedom - 1],
+        _ => 1.960,
+    }
+}
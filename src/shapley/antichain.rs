@@ -0,0 +1,126 @@
+use num_bigint::BigInt;
+use num_traits::{One, Zero};
+use std::collections::HashMap;
+
+// Represents the set of winning coalitions through the antichain of its minimal coalitions. By
+// inclusion-exclusion, a coalition is winning iff
+//     sum over non-empty subsets T of the antichain of (-1)^(|T|+1) * [coalition contains union(T)]
+// equals one. Many subsets share the same union, so the sum is stored as a map from unions to
+// their accumulated coefficient. Unions whose coefficients cancel out are dropped.
+pub struct AntichainCounter {
+    n: usize,
+    union_coefficients: HashMap<u64, BigInt>,
+}
+
+impl AntichainCounter {
+    // Adding a minimal coalition touches every union collected so far, so building the sum costs
+    // about the number of unions times the number of minimal coalitions. Returns None as soon as
+    // this exceeds `max_cost`, in which case enumerating all coalitions is cheaper.
+    pub fn from_minimal_coalitions(
+        minimal_coalitions: &[u64],
+        n: usize,
+        max_cost: usize,
+    ) -> Option<Self> {
+        let mut union_coefficients: HashMap<u64, BigInt> = HashMap::new();
+        for &minimal_coalition in minimal_coalitions {
+            let mut updates = Vec::with_capacity(union_coefficients.len() + 1);
+            for (&union, coefficient) in &union_coefficients {
+                updates.push((union | minimal_coalition, -coefficient));
+            }
+            updates.push((minimal_coalition, BigInt::one()));
+
+            for (union, coefficient) in updates {
+                let entry = union_coefficients.entry(union).or_insert_with(BigInt::zero);
+                *entry += coefficient;
+                if entry.is_zero() {
+                    union_coefficients.remove(&union);
+                }
+            }
+
+            if union_coefficients
+                .len()
+                .saturating_mul(minimal_coalitions.len())
+                > max_cost
+            {
+                return None;
+            }
+        }
+
+        Some(Self {
+            n,
+            union_coefficients,
+        })
+    }
+
+    pub fn union_count(&self) -> usize {
+        self.union_coefficients.len()
+    }
+
+    // Returns, for every group, the number of coalitions of each size (including the group) in
+    // which the group is critical.
+    //
+    // A group i is critical for S ∪ {i} iff S ∪ {i} is winning and S is not. Terms of the sum above
+    // whose union does not contain i contribute equally to both, so only unions u containing i
+    // remain. Each of them is contained in binomial(n - |u|, k - |u|) coalitions of size k that
+    // include i.
+    pub fn critical_counts(&self) -> Vec<Vec<BigInt>> {
+        let n = self.n;
+
+        // coefficient_by_size[i][p]: sum of the coefficients of all unions of size p containing i
+        let mut coefficient_by_size = vec![vec![BigInt::zero(); n + 1]; n];
+        for (&union, coefficient) in &self.union_coefficients {
+            let size = union.count_ones() as usize;
+            for (group, coefficients) in coefficient_by_size.iter_mut().enumerate() {
+                if union & 1 << group != 0 {
+                    coefficients[size] += coefficient;
+                }
+            }
+        }
+
+        let binomials = binomial_table(n);
+        let mut counts = vec![vec![BigInt::zero(); n + 1]; n];
+        for (group, coefficients) in coefficient_by_size.iter().enumerate() {
+            for (size, coefficient) in coefficients.iter().enumerate() {
+                if coefficient.is_zero() {
+                    continue;
+                }
+                for k in size..=n {
+                    counts[group][k] += coefficient * &binomials[n - size][k - size];
+                }
+            }
+        }
+        counts
+    }
+}
+
+// binomials[m][j] = binomial(m, j)
+fn binomial_table(n: usize) -> Vec<Vec<BigInt>> {
+    let mut binomials: Vec<Vec<BigInt>> = Vec::with_capacity(n + 1);
+    for m in 0..=n {
+        let mut row = Vec::with_capacity(m + 1);
+        for j in 0..=m {
+            if j == 0 || j == m {
+                row.push(BigInt::one());
+            } else {
+                row.push(&binomials[m - 1][j - 1] + &binomials[m - 1][j]);
+            }
+        }
+        binomials.push(row);
+    }
+    binomials
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gives_up_once_the_cost_exceeds_the_bound() {
+        // The unions of {0}, {1} and {2} are all seven non-empty coalitions
+        let minimal_coalitions = [0b001, 0b010, 0b100];
+        let counter =
+            AntichainCounter::from_minimal_coalitions(&minimal_coalitions, 3, 21).unwrap();
+        assert_eq!(counter.union_count(), 7);
+        assert!(AntichainCounter::from_minimal_coalitions(&minimal_coalitions, 3, 20).is_none());
+    }
+}
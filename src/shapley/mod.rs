@@ -5,18 +5,46 @@ use num_rational::BigRational;
 use num_traits::cast::ToPrimitive;
 use num_traits::{One, Signed, Zero};
 use rayon::prelude::*;
+use std::collections::HashMap;
 use std::fmt::{Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
 use std::time::Duration;
 
+mod antichain;
 mod game_solving;
+mod owen;
+mod permutation_sampling;
+mod power_indices;
+mod semivalues;
+mod symbolic;
+mod symmetry;
 use crate::cli::ResponsibilityVersion;
 pub use game_solving::StateGroups;
+pub use owen::CoalitionStructure;
+use permutation_sampling::PermutationSamplerState;
 
-#[derive(Copy, Clone, PartialEq, Eq)]
+#[derive(Clone, PartialEq, Eq)]
 pub enum WeightType {
     Shapley,
     Banzhaf,
     Count,
+    DeeganPackel,
+    HollerPackel,
+    Johnston,
+    Beta(BigRational, BigRational), // Beta Shapley with parameters alpha and beta
+    WeightedBanzhaf(BigRational), // Weighted Banzhaf, where every other group joins with the given probability
+    Custom(Vec<BigRational>),     // One weight per coalition size, starting with size 1
+}
+
+impl WeightType {
+    // Power indices are not defined by a weight per coalition size, so they can only be computed
+    // by the exact engine
+    pub fn is_power_index(&self) -> bool {
+        matches!(
+            self,
+            WeightType::DeeganPackel | WeightType::HollerPackel | WeightType::Johnston
+        )
+    }
 }
 
 impl Display for WeightType {
@@ -25,6 +53,27 @@ impl Display for WeightType {
             WeightType::Shapley => f.write_str("Shapley"),
             WeightType::Banzhaf => f.write_str("Banzhaf"),
             WeightType::Count => f.write_str("Count"),
+            WeightType::DeeganPackel => f.write_str("Deegan-Packel"),
+            WeightType::HollerPackel => f.write_str("Holler-Packel"),
+            WeightType::Johnston => f.write_str("Johnston"),
+            WeightType::Beta(alpha, beta) => write!(f, "Beta({}, {})", alpha, beta),
+            WeightType::WeightedBanzhaf(p) => write!(f, "weighted Banzhaf({})", p),
+            WeightType::Custom(_) => f.write_str("custom weights"),
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub enum Estimator {
+    Coalition,   // Draws a coalition size and then a coalition of that size
+    Permutation, // Walks random orders of the groups until the coalition becomes winning
+}
+
+impl Display for Estimator {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Estimator::Coalition => f.write_str("coalition"),
+            Estimator::Permutation => f.write_str("permutation"),
         }
     }
 }
@@ -38,6 +87,17 @@ pub struct ResponsibilityCalculator {
     silent: bool,
     pub sampled_count: usize, // If probabilistic sampling is used, this contains the number of samples after sampling has finished
     responsibility_version: ResponsibilityVersion,
+    seed: u64,
+    dummy_groups: Option<DummyGroups>, // Set if dummy groups were removed from `state_groups`
+    symmetry_representatives: Option<Vec<usize>>, // Set if symmetries were detected, maps every group to the smallest group symmetric to it
+    minimal_coalitions: Option<Vec<u64>>, // Set if the minimal winning coalitions of `state_groups` were already computed
+}
+
+// Keeps track of the groups that were removed as dummies, so that the results can be extended to
+// all groups again.
+struct DummyGroups {
+    all_state_groups: StateGroups,
+    kept_groups: Vec<usize>, // Original index of every group that is still in `state_groups`
 }
 
 pub struct ResponsibilityThreadState {
@@ -66,6 +126,15 @@ impl ResponsibilityCalculator {
         state_grouping: StateGroups,
         responsibility_version: ResponsibilityVersion,
     ) -> Self {
+        if let WeightType::Custom(weights) = &weight_type {
+            if weights.len() != state_grouping.len() {
+                panic!(
+                    "Custom weights must specify one weight per coalition size, but {} weights were given for {} state (groups).",
+                    weights.len(),
+                    state_grouping.len()
+                )
+            }
+        }
         Self {
             game,
             weight_type,
@@ -75,6 +144,10 @@ impl ResponsibilityCalculator {
             silent: false,
             sampled_count: 0,
             responsibility_version,
+            seed: fastrand::u64(..),
+            dummy_groups: None,
+            symmetry_representatives: None,
+            minimal_coalitions: None,
         }
     }
 
@@ -82,6 +155,211 @@ impl ResponsibilityCalculator {
         self.silent = silent;
     }
 
+    pub fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    // Removes all groups that can never change the winner of the game and returns their indices.
+    // A state is such a dummy if it is unreachable, if it is attracted to the bad states even if all
+    // groups are in the coalition, or if it is not attracted even if no group is. In all three
+    // cases, the winner does not depend on its owner. A group is a dummy if all its members are.
+    pub fn exclude_dummy_groups(&mut self) -> Vec<usize> {
+        if self.responsibility_version == ResponsibilityVersion::Optimistic {
+            panic!("Dummy groups can only be excluded for pessimistic or forward responsibility.")
+        }
+        if self.symmetry_representatives.is_some() {
+            panic!("Dummy groups must be excluded before symmetries are detected.")
+        }
+        if self.weight_type.is_power_index() {
+            panic!(
+                "Dummy groups cannot be excluded for the {} index.",
+                self.weight_type
+            )
+        }
+
+        let reachable = self.game.breadth_first_positions();
+        let attracted_without_coalition = self.game.compute_attractor();
+        for group in 0..self.state_groups.len() {
+            self.state_groups.add_to_coalition(&mut self.game, group);
+        }
+        let attracted_with_all_groups = self.game.compute_attractor();
+        for group in 0..self.state_groups.len() {
+            self.state_groups
+                .remove_from_coalition(&mut self.game, group);
+        }
+
+        let is_dummy_state = |state: usize| {
+            reachable[state].is_none()
+                || !attracted_without_coalition[state]
+                || attracted_with_all_groups[state]
+        };
+        let (dummy_groups, kept_groups): (Vec<usize>, Vec<usize>) = (0..self.state_groups.len())
+            .partition(|&group| {
+                self.state_groups
+                    .members(group)
+                    .iter()
+                    .all(|&state| is_dummy_state(state))
+            });
+
+        if !dummy_groups.is_empty() {
+            let kept_state_groups = self.state_groups.subset(&kept_groups);
+            self.dummy_groups = Some(DummyGroups {
+                all_state_groups: std::mem::replace(&mut self.state_groups, kept_state_groups),
+                kept_groups,
+            });
+            self.minimal_coalitions = None;
+        }
+        dummy_groups
+    }
+
+    // Detects groups that are mapped onto each other by an automorphism of the game. Symmetric groups
+    // have the same responsibility, so the exact engines only compute it for the smallest group of
+    // each class and the stochastic engine averages its estimates over the class. Returns all
+    // classes with more than one group.
+    pub fn detect_symmetries(&mut self) -> Vec<Vec<usize>> {
+        if self.responsibility_version == ResponsibilityVersion::Optimistic {
+            panic!("Symmetries can only be exploited for pessimistic or forward responsibility.")
+        }
+        let representatives =
+            symmetry::SymmetryDetector::new(&self.game, &self.state_groups).find_representatives();
+
+        let mut classes = vec![Vec::new(); representatives.len()];
+        for (group, &representative) in representatives.iter().enumerate() {
+            classes[representative].push(group);
+        }
+        self.symmetry_representatives = Some(representatives);
+        classes.retain(|class| class.len() > 1);
+        classes
+    }
+
+    fn is_symmetry_representative(&self, group: usize) -> bool {
+        match &self.symmetry_representatives {
+            Some(representatives) => representatives[group] == group,
+            None => true,
+        }
+    }
+
+    // Applied to the results of every engine for pessimistic responsibility before they are returned
+    fn finish_results(
+        &mut self,
+        results: Vec<ResponsibilityResult>,
+        is_estimate: bool,
+    ) -> Vec<ResponsibilityResult> {
+        let results = self.share_symmetric_results(results, is_estimate);
+        self.include_dummy_groups(results)
+    }
+
+    // Gives every group of a symmetry class the same counts: exact results are copied from the
+    // representative, which is the only group of the class they were computed for, while estimates
+    // are averaged over the class.
+    fn share_symmetric_results(
+        &self,
+        results: Vec<ResponsibilityResult>,
+        is_estimate: bool,
+    ) -> Vec<ResponsibilityResult> {
+        let representatives = match &self.symmetry_representatives {
+            Some(representatives) => representatives,
+            None => return results,
+        };
+
+        let n = self.state_groups.len();
+        let mut class_results = (0..n)
+            .map(|group| ResponsibilityResult::new(group, n))
+            .collect::<Vec<_>>();
+        let mut class_sizes = vec![0usize; n];
+        for result in &results {
+            let representative = representatives[result.group_index];
+            class_sizes[representative] += 1;
+            if is_estimate || representative == result.group_index {
+                class_results[representative].add_counts(result);
+            }
+        }
+        if is_estimate {
+            for (class_result, &class_size) in class_results.iter_mut().zip(class_sizes.iter()) {
+                if class_size > 1 {
+                    let class_size = BigRational::from_integer(class_size.into());
+                    for count in &mut class_result.count_by_size {
+                        *count /= &class_size;
+                    }
+                }
+            }
+        }
+
+        let mut shared_results = Vec::with_capacity(results.len());
+        if is_estimate {
+            let weights = ResponsibilityResult::compute_weights(&self.weight_type, n);
+            for result in &results {
+                let mut shared_result = ResponsibilityResult::new(result.group_index, n);
+                shared_result.add_counts(&class_results[representatives[result.group_index]]);
+                shared_result.compute_values(&weights[..]);
+                shared_results.push(shared_result);
+            }
+        } else {
+            let result_of_group = results
+                .iter()
+                .map(|result| (result.group_index, result))
+                .collect::<HashMap<_, _>>();
+            for result in &results {
+                let mut shared_result =
+                    result_of_group[&representatives[result.group_index]].clone();
+                shared_result.group_index = result.group_index;
+                shared_results.push(shared_result);
+            }
+        }
+        shared_results
+    }
+
+    // Restores the groups removed by `exclude_dummy_groups` and extends the results to them. Adding
+    // dummies to a coalition does not change whether it is winning, so a coalition of size k in
+    // which a group is critical gives binomial(d, j) critical coalitions of size k + j once the d
+    // dummies are included.
+    fn include_dummy_groups(
+        &mut self,
+        results: Vec<ResponsibilityResult>,
+    ) -> Vec<ResponsibilityResult> {
+        let dummy_groups = match self.dummy_groups.take() {
+            Some(dummy_groups) => dummy_groups,
+            None => return results,
+        };
+        self.state_groups = dummy_groups.all_state_groups;
+        self.minimal_coalitions = None;
+
+        let n = self.state_groups.len();
+        let dummy_count = n - dummy_groups.kept_groups.len();
+        let mut binomials = Vec::with_capacity(dummy_count + 1);
+        let mut current_value = BigInt::one();
+        binomials.push(current_value.clone());
+        for j in 1..=dummy_count {
+            current_value = current_value * (dummy_count + 1 - j) / j;
+            binomials.push(current_value.clone());
+        }
+
+        let weights = ResponsibilityResult::compute_weights(&self.weight_type, n);
+        let mut all_results = (0..n)
+            .map(|group| ResponsibilityResult::new(group, n))
+            .collect::<Vec<_>>();
+        for result in results {
+            let all_result = &mut all_results[dummy_groups.kept_groups[result.group_index]];
+            for (size, count) in result.count_by_size.iter().enumerate() {
+                if count.is_zero() {
+                    continue;
+                }
+                for (j, binomial) in binomials.iter().enumerate() {
+                    all_result.count_by_size[size + j] +=
+                        count * BigRational::from_integer(binomial.clone());
+                }
+            }
+        }
+        for result in &mut all_results {
+            result.compute_values(&weights[..]);
+        }
+        all_results
+    }
+
     fn compute_optimistic_responsibility(&mut self) -> Vec<ResponsibilityResult> {
         for state in &mut self.game.states {
             if state.default_owner != Player::Path {
@@ -115,8 +393,25 @@ impl ResponsibilityCalculator {
             }
         }
 
-        let weights =
-            ResponsibilityResult::compute_weights(self.weight_type, self.state_groups.len());
+        let weights = match &self.weight_type {
+            WeightType::DeeganPackel | WeightType::HollerPackel => {
+                // Every winning group forms a minimal winning coalition on its own
+                for resp in &mut res {
+                    resp.count_by_size = vec![BigRational::zero(); self.state_groups.len() + 1];
+                    resp.count_by_size[1] = BigRational::one();
+                }
+                power_indices::uniform_weights(number_winning.into(), self.state_groups.len())
+            }
+            WeightType::Johnston => {
+                // The coalitions counted above are exactly the vulnerable coalitions, and the
+                // winning group is the only critical group in each of them
+                power_indices::uniform_weights(
+                    BigInt::from(number_winning) * BigInt::from(2).pow(number_losing as u32),
+                    self.state_groups.len(),
+                )
+            }
+            _ => ResponsibilityResult::compute_weights(&self.weight_type, self.state_groups.len()),
+        };
 
         for resp in &mut res {
             resp.compute_values(&weights);
@@ -125,25 +420,269 @@ impl ResponsibilityCalculator {
         res
     }
 
-    pub fn compute_individual_responsibility(&mut self) -> Vec<ResponsibilityResult> {
-        if self.responsibility_version == ResponsibilityVersion::Optimistic {
-            return self.compute_optimistic_responsibility();
-        }
+    fn prepare_game_solver(&self) -> game_solving::CachedGameSolver<'_> {
         let mut game_solver = game_solving::CachedGameSolver::new(
             self.game.clone(),
             self.thread_count,
             &self.state_groups,
         );
         game_solver.set_silent(self.silent);
-        game_solver.prepare();
+        match &self.minimal_coalitions {
+            Some(minimal_coalitions) => {
+                game_solver.set_minimal_coalitions(minimal_coalitions.clone())
+            }
+            None => game_solver.prepare(),
+        }
+        game_solver
+    }
+
+    pub fn compute_individual_responsibility(&mut self) -> Vec<ResponsibilityResult> {
+        if self.responsibility_version == ResponsibilityVersion::Optimistic {
+            return self.compute_optimistic_responsibility();
+        }
+        let game_solver = self.prepare_game_solver();
+        let results = if self.weight_type.is_power_index() {
+            self.compute_power_index(&game_solver)
+        } else {
+            self.count_critical_coalitions(&game_solver)
+        };
+        self.finish_results(results, false)
+    }
+
+    // Computes the Owen value of every state group, where the unions of the coalition structure
+    // first share the responsibility among themselves and then among their members.
+    pub fn compute_owen_values(
+        &mut self,
+        coalition_structure: &CoalitionStructure,
+    ) -> Vec<ResponsibilityResult> {
+        if self.responsibility_version == ResponsibilityVersion::Optimistic {
+            panic!("The Owen value is only supported for pessimistic or forward responsibility.")
+        }
+        if self.dummy_groups.is_some() || self.symmetry_representatives.is_some() {
+            panic!("The Owen value cannot be combined with excluding dummies or with symmetry reduction, as both ignore the unions.")
+        }
+        if self.weight_type.is_power_index() || matches!(self.weight_type, WeightType::Custom(_)) {
+            panic!(
+                "The Owen value requires weights that are defined for any number of players, which is not the case for {}.",
+                self.weight_type
+            )
+        }
+        let game_solver = self.prepare_game_solver();
+
+        let start_responsibility_time = std::time::Instant::now();
+        let results = coalition_structure.compute_values(&game_solver, &self.weight_type);
+        if !self.silent {
+            println!(
+                "Computed the Owen value for {} unions in {:.2?}.",
+                coalition_structure.unions().len(),
+                start_responsibility_time.elapsed()
+            );
+            println!();
+        }
+        results
+    }
+
+    fn compute_power_index(
+        &self,
+        game_solver: &game_solving::CachedGameSolver,
+    ) -> Vec<ResponsibilityResult> {
+        let start_responsibility_time = std::time::Instant::now();
+        let group_count = game_solver.state_groups.len();
+        let power_index = power_indices::PowerIndexCounts::compute(
+            &self.weight_type,
+            game_solver,
+            self.thread_count,
+            self.step_size,
+            self.silent,
+        );
+
+        let mut results = Vec::with_capacity(group_count);
+        for (i, counts) in power_index.counts.into_iter().enumerate() {
+            let mut result = ResponsibilityResult::new(i, group_count);
+            result.count_by_size = counts;
+            result.compute_values(&power_index.weights[..]);
+            results.push(result);
+        }
+
+        if !self.silent {
+            println!(
+                "Computed the {} index in {:.2?}.",
+                self.weight_type,
+                start_responsibility_time.elapsed()
+            );
+            println!();
+        }
+        results
+    }
+
+    fn assert_weights_per_size(&self, engine: &str) {
+        if self.weight_type.is_power_index() {
+            panic!(
+                "The {} index is only supported by the exact engine, not by the {}.",
+                self.weight_type, engine
+            )
+        }
+    }
+
+    // Returns the winning coalitions of minimum total cost, given the cost of every state group,
+    // together with that cost. As costs are not negative, the cheapest winning coalitions can be
+    // found among the minimal ones. Returns None if even the coalition of all groups loses.
+    // The costs are given for the groups in `state_groups`, but the coalitions contain the original
+    // group indices if dummy groups were excluded. The minimal coalitions are kept, so that an engine
+    // that runs afterwards does not have to compute them again.
+    pub fn cheapest_fixes(
+        &mut self,
+        costs: &[BigRational],
+    ) -> Option<(BigRational, Vec<Vec<usize>>)> {
+        if self.responsibility_version == ResponsibilityVersion::Optimistic {
+            panic!("Cheapest fixes are only supported for pessimistic or forward responsibility.")
+        }
+        let minimal_coalitions = self.prepare_game_solver().minimal_coalitions().to_vec();
+        let group_count = self.state_groups.len();
+        let original_group = |group: usize| match &self.dummy_groups {
+            Some(dummy_groups) => dummy_groups.kept_groups[group],
+            None => group,
+        };
+        let mut cheapest: Option<(BigRational, Vec<u64>)> = None;
+        for &coalition in &minimal_coalitions {
+            let cost = (0..group_count)
+                .filter(|&group| coalition & 1 << group != 0)
+                .map(|group| &costs[group])
+                .sum::<BigRational>();
+            match &mut cheapest {
+                Some((min_cost, coalitions)) if cost == *min_cost => coalitions.push(coalition),
+                Some((min_cost, _)) if cost > *min_cost => {}
+                _ => cheapest = Some((cost, vec![coalition])),
+            }
+        }
+        let cheapest = cheapest.map(|(cost, mut coalitions)| {
+            coalitions.sort();
+            let coalitions = coalitions
+                .into_iter()
+                .map(|coalition| {
+                    (0..group_count)
+                        .filter(|&group| coalition & 1 << group != 0)
+                        .map(original_group)
+                        .collect()
+                })
+                .collect();
+            (cost, coalitions)
+        });
+        self.minimal_coalitions = Some(minimal_coalitions);
+        cheapest
+    }
+
+    // Computes the responsibility directly from the minimal winning coalitions instead of checking
+    // every coalition. This is much faster if there are few minimal coalitions, but falls back to
+    // enumerating all coalitions if there are too many of them.
+    pub fn compute_responsibility_from_antichain(&mut self) -> Vec<ResponsibilityResult> {
+        self.assert_weights_per_size("antichain engine");
+        if self.responsibility_version == ResponsibilityVersion::Optimistic {
+            return self.compute_optimistic_responsibility();
+        }
+        let game_solver = self.prepare_game_solver();
+        let group_count = game_solver.state_groups.len();
+
+        let start_responsibility_time = std::time::Instant::now();
+
+        // Enumerating all coalitions checks every group for each of them
+        let enumeration_cost = 1usize
+            .checked_shl(group_count as u32)
+            .unwrap_or(usize::MAX)
+            .saturating_mul(group_count);
+        let counter = match antichain::AntichainCounter::from_minimal_coalitions(
+            game_solver.minimal_coalitions(),
+            group_count,
+            enumeration_cost,
+        ) {
+            Some(counter) => counter,
+            None => {
+                if !self.silent {
+                    println!("The minimal coalitions have too many unions, enumerating all coalitions instead.");
+                }
+                let results = self.count_critical_coalitions(&game_solver);
+                return self.finish_results(results, false);
+            }
+        };
+
+        let weights = ResponsibilityResult::compute_weights(&self.weight_type, group_count);
+        let mut results = Vec::with_capacity(group_count);
+        for (i, counts) in counter.critical_counts().into_iter().enumerate() {
+            let mut result = ResponsibilityResult::new(i, group_count);
+            for (size, count) in counts.into_iter().enumerate() {
+                result.count_by_size[size] = BigRational::from_integer(count);
+            }
+            result.compute_values(&weights[..]);
+            results.push(result);
+        }
+
+        if !self.silent {
+            println!(
+                "Computed responsibility from {} unions of minimal coalitions in {:.2?}.",
+                counter.union_count(),
+                start_responsibility_time.elapsed()
+            );
+            println!();
+        }
+        self.finish_results(results, false)
+    }
+
+    // Represents the winning coalitions as a BDD computed by a symbolic attractor and counts the
+    // critical coalitions on it. Unlike the other exact engines, this does not enumerate
+    // coalitions and is therefore not limited to 64 state (groups).
+    pub fn compute_responsibility_symbolically(&mut self) -> Vec<ResponsibilityResult> {
+        self.assert_weights_per_size("symbolic engine");
+        if self.responsibility_version == ResponsibilityVersion::Optimistic {
+            return self.compute_optimistic_responsibility();
+        }
+        let group_count = self.state_groups.len();
+
+        let start_time = std::time::Instant::now();
+        let mut counter = symbolic::SymbolicCounter::from_game(&self.game, &self.state_groups);
+        if !self.silent {
+            println!(
+                "Computed the winning coalitions of {} state (groups) as a BDD with {} nodes in {:.2?}.",
+                group_count,
+                counter.node_count(),
+                start_time.elapsed()
+            );
+        }
 
+        let start_responsibility_time = std::time::Instant::now();
+        let weights = ResponsibilityResult::compute_weights(&self.weight_type, group_count);
+        let mut results = Vec::with_capacity(group_count);
+        let critical_counts =
+            counter.critical_counts(|group| self.is_symmetry_representative(group));
+        for (i, counts) in critical_counts.into_iter().enumerate() {
+            let mut result = ResponsibilityResult::new(i, group_count);
+            for (size, count) in counts.into_iter().enumerate() {
+                result.count_by_size[size] = BigRational::from_integer(count);
+            }
+            result.compute_values(&weights[..]);
+            results.push(result);
+        }
+
+        if !self.silent {
+            println!(
+                "Computed responsibility in {:.2?}.",
+                start_responsibility_time.elapsed()
+            );
+            println!();
+        }
+        self.finish_results(results, false)
+    }
+
+    fn count_critical_coalitions(
+        &self,
+        game_solver: &game_solving::CachedGameSolver,
+    ) -> Vec<ResponsibilityResult> {
         let group_count = game_solver.state_groups.len();
 
         let start_responsibility_time = std::time::Instant::now();
 
         let mut thread_states = Vec::with_capacity(self.thread_count);
         for i in 0..self.thread_count {
-            thread_states.push(ResponsibilityThreadState::new(&game_solver.state_groups, i));
+            thread_states.push(ResponsibilityThreadState::new(game_solver.state_groups, i));
         }
 
         let coalition_count = 1 << group_count;
@@ -163,6 +702,9 @@ impl ResponsibilityCalculator {
                 let size = base_coalition.count_ones() as usize;
                 if !game_solver.is_game_winning(base_coalition) {
                     for added_state in 0..group_count {
+                        if !self.is_symmetry_representative(added_state) {
+                            continue;
+                        }
                         let coalition = base_coalition | 1 << added_state;
                         if coalition != base_coalition && game_solver.is_game_winning(coalition) {
                             thread_state.results[added_state].count_by_size[size + 1] +=
@@ -182,7 +724,7 @@ impl ResponsibilityCalculator {
         progress_reporter.lock().unwrap().set_finished();
 
         let mut results = Vec::with_capacity(group_count);
-        let weights = ResponsibilityResult::compute_weights(self.weight_type, group_count);
+        let weights = ResponsibilityResult::compute_weights(&self.weight_type, group_count);
 
         for i in 0..game_solver.state_groups.len() {
             let mut result = ResponsibilityResult::new(i, group_count);
@@ -206,12 +748,22 @@ impl ResponsibilityCalculator {
     pub fn sample_individual_responsibilities(
         &mut self,
         sample_target: SampleTarget,
+        estimator: Estimator,
     ) -> Vec<ResponsibilityResult> {
-        let start_time = std::time::Instant::now();
-
-        if self.responsibility_version != ResponsibilityVersion::Pessimistic {
-            panic!("The randomized algorithm only supports pessimistic responsibility. For optimistic responsibility, it provides no benefit over the exact algorithm.")
+        if self.responsibility_version == ResponsibilityVersion::Optimistic {
+            panic!("The randomized algorithm only supports pessimistic and forward responsibility. For optimistic responsibility, it provides no benefit over the exact algorithm.")
         }
+        self.assert_weights_per_size("stochastic engine");
+
+        let results = match estimator {
+            Estimator::Coalition => self.sample_coalitions(sample_target),
+            Estimator::Permutation => self.sample_permutations(sample_target),
+        };
+        self.finish_results(results, true)
+    }
+
+    fn sample_coalitions(&mut self, sample_target: SampleTarget) -> Vec<ResponsibilityResult> {
+        let start_time = std::time::Instant::now();
 
         if !self.silent {
             println!(
@@ -221,22 +773,18 @@ impl ResponsibilityCalculator {
         }
 
         let mut thread_states = Vec::with_capacity(self.thread_count);
-        for i in 0..self.thread_count {
-            thread_states.push(SamplerState::new(
-                self.game.clone(),
-                &self.state_groups,
-                sample_target.split_across_threads(self.thread_count, i),
-                self.thread_count,
-            ));
+        for _ in 0..self.thread_count {
+            thread_states.push(SamplerState::new(self.game.clone(), &self.state_groups));
         }
         if !self.silent {
             thread_states[0].add_progress_bar(sample_target);
         }
 
         let samples_per_winning = 50;
+        let chunks = SampleChunks::new(self.seed, sample_target);
         thread_states
             .par_iter_mut()
-            .for_each(|s| s.run(samples_per_winning));
+            .for_each(|s| s.run(&chunks, samples_per_winning));
 
         if !self.silent {
             println!("Collecting thread results.");
@@ -258,11 +806,59 @@ impl ResponsibilityCalculator {
         }
         self.sampled_count = result.total_samples;
 
-        result.to_responsibility_results(self.weight_type, samples_per_winning)
+        result.to_responsibility_results(&self.weight_type, samples_per_winning)
+    }
+
+    fn sample_permutations(&mut self, sample_target: SampleTarget) -> Vec<ResponsibilityResult> {
+        let start_time = std::time::Instant::now();
+
+        if !self.silent {
+            println!(
+                "Sampling random orders of the state (groups) (there are {} state (groups))",
+                self.state_groups.len()
+            );
+        }
+
+        let mut thread_states = Vec::with_capacity(self.thread_count);
+        for _ in 0..self.thread_count {
+            thread_states.push(PermutationSamplerState::new(
+                self.game.clone(),
+                &self.state_groups,
+            ));
+        }
+        if !self.silent {
+            thread_states[0].add_progress_bar(sample_target);
+        }
+
+        let chunks = SampleChunks::new(self.seed, sample_target);
+        thread_states.par_iter_mut().for_each(|s| s.run(&chunks));
+
+        if !self.silent {
+            println!("Collecting thread results.");
+        }
+
+        let result = thread_states
+            .into_par_iter()
+            .reduce_with(|mut x, y| {
+                x.add_values(&y);
+                x
+            })
+            .unwrap();
+        if !self.silent {
+            println!(
+                "Sampled {} orders in {:.2?}.",
+                result.total_samples(),
+                start_time.elapsed()
+            );
+        }
+        self.sampled_count = result.total_samples();
+
+        result.to_responsibility_results(&self.weight_type)
     }
 }
 
 pub struct TrimmedResponsibilityResult {
+    #[allow(dead_code)]
     pub group_index: usize,
     _n: usize,
     pub total_value: BigRational,
@@ -278,6 +874,7 @@ impl TrimmedResponsibilityResult {
     }
 }
 
+#[derive(Clone)]
 pub struct ResponsibilityResult {
     pub group_index: usize,
     n: usize,
@@ -310,7 +907,7 @@ impl ResponsibilityResult {
         }
     }
 
-    pub fn compute_weights(weight_type: WeightType, n: usize) -> Vec<BigRational> {
+    pub fn compute_weights(weight_type: &WeightType, n: usize) -> Vec<BigRational> {
         let mut factorials = Vec::with_capacity(n + 1);
         let mut current_value = BigInt::one();
         factorials.push(BigInt::one());
@@ -331,15 +928,32 @@ impl ResponsibilityResult {
                     BigRational::new(1.into(), BigInt::from(2).pow(n as u32 - 1))
                 }
                 WeightType::Count => BigRational::one(),
+                WeightType::DeeganPackel | WeightType::HollerPackel | WeightType::Johnston => {
+                    panic!(
+                        "The {} index is only supported by the exact engine.",
+                        weight_type
+                    )
+                }
+                WeightType::Beta(alpha, beta) => semivalues::beta_weight(alpha, beta, i, n),
+                WeightType::WeightedBanzhaf(p) => semivalues::weighted_banzhaf_weight(p, i, n),
+                // If dummy groups are excluded, the engines compute intermediate values for fewer
+                // groups, which are recomputed with all weights once the dummies are included
+                WeightType::Custom(custom_weights) => custom_weights[i - 1].clone(),
             })
         }
         weights
     }
 
     pub fn compute_values(&mut self, weights: &[BigRational]) {
-        for i in 1..=self.n {
-            self.value_by_size[i] = self.count_by_size[i].clone() * weights[i].clone();
-            self.total_value += self.value_by_size[i].clone();
+        for ((value, count), weight) in self
+            .value_by_size
+            .iter_mut()
+            .zip(&self.count_by_size)
+            .zip(weights)
+            .skip(1)
+        {
+            *value = count.clone() * weight.clone();
+            self.total_value += value.clone();
         }
     }
 
@@ -359,17 +973,76 @@ pub enum SampleTarget {
     ElapsedTime(Duration),
 }
 
-impl SampleTarget {
-    pub fn split_across_threads(&self, thread_count: usize, thread_index: usize) -> Self {
-        match self {
-            Self::Samples(sample_count) => {
-                if thread_index < sample_count % thread_count {
-                    Self::Samples(sample_count / thread_count + 1)
-                } else {
-                    Self::Samples(sample_count / thread_count)
+// Hands out the samples in chunks of fixed size. Every chunk has its own random generator that is
+// derived from the seed and the index of the chunk, so the samples that are drawn do not depend on
+// the number of threads or on which thread processes which chunk. For a fixed sample count, the
+// same seed therefore always gives the same result.
+pub struct SampleChunks {
+    seed: u64,
+    target: SampleTarget,
+    next_chunk: AtomicUsize,
+    start_time: std::time::Instant,
+}
+
+pub struct SampleChunk {
+    pub index: usize,
+    pub samples: usize,
+    pub rng: fastrand::Rng,
+}
+
+impl SampleChunks {
+    const CHUNK_SIZE: usize = 64;
+
+    pub fn new(seed: u64, target: SampleTarget) -> Self {
+        Self {
+            seed,
+            target,
+            next_chunk: AtomicUsize::new(0),
+            start_time: std::time::Instant::now(),
+        }
+    }
+
+    pub fn next_chunk(&self) -> Option<SampleChunk> {
+        let samples = match self.target {
+            SampleTarget::Samples(sample_target) => {
+                let index = self.next_chunk.fetch_add(1, Ordering::Relaxed);
+                let first_sample = index * Self::CHUNK_SIZE;
+                if first_sample >= sample_target {
+                    return None;
                 }
+                return Some(self.chunk(index, Self::CHUNK_SIZE.min(sample_target - first_sample)));
             }
-            Self::ElapsedTime(duration) => Self::ElapsedTime(*duration),
+            SampleTarget::ElapsedTime(target_duration) => {
+                if self.start_time.elapsed() >= target_duration {
+                    return None;
+                }
+                Self::CHUNK_SIZE
+            }
+        };
+        let index = self.next_chunk.fetch_add(1, Ordering::Relaxed);
+        Some(self.chunk(index, samples))
+    }
+
+    fn chunk(&self, index: usize, samples: usize) -> SampleChunk {
+        SampleChunk {
+            index,
+            samples,
+            rng: fastrand::Rng::with_seed(Self::chunk_seed(self.seed, index as u64)),
+        }
+    }
+
+    // SplitMix64 finaliser, so that neighbouring chunks get unrelated generator states
+    fn chunk_seed(seed: u64, index: u64) -> u64 {
+        let mut z = seed ^ index.wrapping_mul(0x9E3779B97F4A7C15);
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    pub fn progress(&self, chunk: &SampleChunk) -> u64 {
+        match self.target {
+            SampleTarget::Samples(_) => (chunk.index * Self::CHUNK_SIZE) as u64,
+            SampleTarget::ElapsedTime(_) => (self.start_time.elapsed().as_millis() / 100) as u64,
         }
     }
 }
@@ -379,28 +1052,23 @@ pub struct SamplerState<'a> {
     state_groups: &'a StateGroups,
     total_samples: usize,
     samples_per_weight_global: Vec<usize>, // Samples that count for all states (i.e. those where the winning coalition was insignificant)
-    samples_per_weight_local: Vec<Vec<BigRational>>, // Samples that only count for individual states. Can be negative if a sample counts for all except this state
+    checked_per_weight: Vec<Vec<usize>>, // Members of winning coalitions that were removed to check whether they are critical
+    unchecked_per_weight: Vec<Vec<usize>>, // Members of winning coalitions that were not checked
     significant_per_weight: Vec<Vec<usize>>,
-    target: SampleTarget,
     progress_bar: Option<SamplesProgressReporter>,
-    thread_count: usize,
-    seed: u64,
 }
 
 impl<'a> SamplerState<'a> {
-    pub fn new(
-        game: Game,
-        state_groups: &'a StateGroups,
-        target: SampleTarget,
-        thread_count: usize,
-    ) -> SamplerState<'a> {
+    pub fn new(game: Game, state_groups: &'a StateGroups) -> SamplerState<'a> {
         let size = state_groups.len();
         let mut samples_per_weight_global = Vec::with_capacity(size + 1);
-        let mut samples_per_weight_local = Vec::with_capacity(size + 1);
+        let mut checked_per_weight = Vec::with_capacity(size + 1);
+        let mut unchecked_per_weight = Vec::with_capacity(size + 1);
         let mut significant_per_weight = Vec::with_capacity(size + 1);
         for _ in 0..=size {
             samples_per_weight_global.push(0);
-            samples_per_weight_local.push(vec![BigRational::zero(); size]);
+            checked_per_weight.push(vec![0; size]);
+            unchecked_per_weight.push(vec![0; size]);
             significant_per_weight.push(vec![0; size]);
         }
 
@@ -409,54 +1077,50 @@ impl<'a> SamplerState<'a> {
             state_groups,
             total_samples: 0,
             samples_per_weight_global,
-            samples_per_weight_local,
+            checked_per_weight,
+            unchecked_per_weight,
             significant_per_weight,
-            target,
             progress_bar: None,
-            thread_count,
-            seed: fastrand::u64(..),
         }
     }
 
     pub fn add_progress_bar(&mut self, target: SampleTarget) {
-        self.progress_bar = Some(match target {
-            SampleTarget::Samples(samples) => {
-                SamplesProgressReporter::new_with_count(samples as u64)
-            }
-            SampleTarget::ElapsedTime(duration) => {
-                SamplesProgressReporter::new_with_duration((duration.as_millis() / 100) as u64)
-            }
-        });
+        self.progress_bar = Some(SamplesProgressReporter::from_target(target));
     }
 
-    pub fn get_samples(&self, size: usize, state: usize) -> BigRational {
-        BigRational::from_integer(self.samples_per_weight_global[size].into())
-            + &self.samples_per_weight_local[size][state]
+    pub fn get_samples(
+        &self,
+        size: usize,
+        state: usize,
+        samples_per_winning: usize,
+    ) -> BigRational {
+        // Every member of a winning coalition is only checked with probability checked/size, so
+        // each check counts size/checked times. Because every coalition already adds one global
+        // sample, a check adds (size-checked)/checked local samples, while a member that was not
+        // checked removes its global sample again.
+        if size == 0 {
+            return BigRational::zero(); // The empty coalition has no members
+        }
+        let checked = size.min(samples_per_winning);
+        BigRational::new(
+            BigInt::from(self.samples_per_weight_global[size]) * checked
+                + BigInt::from(self.checked_per_weight[size][state]) * (size - checked)
+                - BigInt::from(self.unchecked_per_weight[size][state]) * checked,
+            checked.into(),
+        )
     }
 
-    pub fn run(&mut self, samples_per_winning: usize) {
-        let start_time = std::time::Instant::now();
-        fastrand::seed(self.seed);
-
-        loop {
-            let new_progress_value = match self.target {
-                SampleTarget::Samples(sample_target) => {
-                    if self.total_samples >= sample_target {
-                        break;
-                    }
-                    (self.total_samples * self.thread_count) as u64
-                }
-                SampleTarget::ElapsedTime(target_duration) => {
-                    if start_time.elapsed() >= target_duration {
-                        break;
-                    }
-                    (start_time.elapsed().as_millis() / 100) as u64
-                }
-            };
+    pub fn run(&mut self, chunks: &SampleChunks, samples_per_winning: usize) {
+        while let Some(mut chunk) = chunks.next_chunk() {
             if let Some(progress_bar) = &mut self.progress_bar {
-                progress_bar.set_current_size(new_progress_value);
+                progress_bar.set_current_size(chunks.progress(&chunk));
+            }
+            for _ in 0..chunk.samples {
+                self.sample(
+                    &mut chunk.rng,
+                    self.state_groups.len().min(samples_per_winning),
+                );
             }
-            self.sample(self.state_groups.len().min(samples_per_winning));
         }
         if let Some(progress_bar) = &mut self.progress_bar {
             progress_bar.finish();
@@ -464,11 +1128,11 @@ impl<'a> SamplerState<'a> {
         }
     }
 
-    pub fn sample(&mut self, samples_if_winning: usize) {
-        let size = fastrand::usize(1..=self.state_groups.len());
+    pub fn sample(&mut self, rng: &mut fastrand::Rng, samples_if_winning: usize) {
+        let size = rng.usize(1..=self.state_groups.len());
         let samples_if_winning = samples_if_winning.min(size);
 
-        let members = self.sample_coalition(size);
+        let members = self.sample_coalition(rng, size);
 
         for &member in &members {
             self.state_groups.add_to_coalition(&mut self.game, member);
@@ -478,13 +1142,9 @@ impl<'a> SamplerState<'a> {
 
         if winner == Player::Safe {
             let mut remaining_samples = samples_if_winning;
-            // The share of members sampled is samples/size, so each sample counts size/sample.
-            // Because we add one global sample (see below), we need to add size/sample - 1 local
-            // counts, which is equal to (size-sample)/sample
-            let sample_factor =
-                BigRational::new((size - remaining_samples).into(), remaining_samples.into());
+            // The counts are only weighted when the results are collected (see `get_samples`)
             for (i, &member) in members.iter().enumerate() {
-                if fastrand::f64() % 1.0 < remaining_samples as f64 / (size - i) as f64 {
+                if rng.f64() % 1.0 < remaining_samples as f64 / (size - i) as f64 {
                     remaining_samples -= 1;
                     self.state_groups
                         .remove_from_coalition(&mut self.game, member);
@@ -492,10 +1152,10 @@ impl<'a> SamplerState<'a> {
                     if self.game.determine_winner() != winner {
                         self.significant_per_weight[size][member] += 1;
                     }
-                    self.samples_per_weight_local[size][member] += &sample_factor;
+                    self.checked_per_weight[size][member] += 1;
                     self.state_groups.add_to_coalition(&mut self.game, member);
                 } else {
-                    self.samples_per_weight_local[size][member] -= BigRational::one();
+                    self.unchecked_per_weight[size][member] += 1;
                 }
             }
         }
@@ -509,13 +1169,11 @@ impl<'a> SamplerState<'a> {
         self.total_samples += 1;
     }
 
-    fn sample_coalition(&self, size: usize) -> Vec<usize> {
+    fn sample_coalition(&self, rng: &mut fastrand::Rng, size: usize) -> Vec<usize> {
         let mut members = Vec::new();
         let mut remaining_members = size;
         for i in 0..self.state_groups.len() {
-            if fastrand::f64() % 1.0
-                <= remaining_members as f64 / (self.state_groups.len() - i) as f64
-            {
+            if rng.f64() % 1.0 <= remaining_members as f64 / (self.state_groups.len() - i) as f64 {
                 members.push(i);
                 remaining_members -= 1;
             }
@@ -528,7 +1186,8 @@ impl<'a> SamplerState<'a> {
         for i in 0..self.samples_per_weight_global.len() {
             self.samples_per_weight_global[i] += other.samples_per_weight_global[i];
             for j in 0..self.significant_per_weight[i].len() {
-                self.samples_per_weight_local[i][j] += &other.samples_per_weight_local[i][j];
+                self.checked_per_weight[i][j] += other.checked_per_weight[i][j];
+                self.unchecked_per_weight[i][j] += other.unchecked_per_weight[i][j];
                 self.significant_per_weight[i][j] += other.significant_per_weight[i][j];
             }
         }
@@ -536,7 +1195,7 @@ impl<'a> SamplerState<'a> {
 
     pub fn to_responsibility_results(
         &self,
-        weight_type: WeightType,
+        weight_type: &WeightType,
         samples_per_winning: usize,
     ) -> Vec<ResponsibilityResult> {
         let mut results = Vec::with_capacity(self.state_groups.len());
@@ -552,7 +1211,7 @@ impl<'a> SamplerState<'a> {
             significant_factor.push(BigRational::new(size.into(), samples.into()));
         }
 
-        if weight_type == WeightType::Shapley {
+        if *weight_type == WeightType::Shapley {
             // In the shapley case, we use the following optimisation:
             // Instead of first estimating the count per size and weights per size and using this to
             // calculate the value per size, we directly estimate the weight per size. This works
@@ -560,11 +1219,15 @@ impl<'a> SamplerState<'a> {
             // For each size, we have
             //     significant_samples/total_samples * coalitions_of_size * shapley_weight_per_item
             //   = significant_samples/total_samples / size
+            // The counts are still derived from the values afterwards, as results are combined
+            // based on their counts (e.g. when dummy groups are included again).
+            let weights =
+                ResponsibilityResult::compute_weights(weight_type, self.state_groups.len());
 
             for index in 0..self.state_groups.len() {
                 let mut result = ResponsibilityResult::new(index, self.state_groups.len());
                 for size in 1..=self.state_groups.len() {
-                    let samples = self.get_samples(size, index);
+                    let samples = self.get_samples(size, index, samples_per_winning);
                     if samples.is_positive() {
                         result.value_by_size[size] = BigRational::new(
                             self.significant_per_weight[size][index].into(),
@@ -573,6 +1236,7 @@ impl<'a> SamplerState<'a> {
                             / &samples;
 
                         result.total_value += &result.value_by_size[size];
+                        result.count_by_size[size] = &result.value_by_size[size] / &weights[size];
                         // println!(
                         //     "Group {}, size {}: {} * {} significant out of {}, value: {}",
                         //     index,
@@ -605,7 +1269,7 @@ impl<'a> SamplerState<'a> {
                     / (factorials[size].clone()
                         * factorials[self.state_groups.len() - size].clone());
                 for state in 0..self.state_groups.len() {
-                    let samples = self.get_samples(size, state);
+                    let samples = self.get_samples(size, state, samples_per_winning);
                     let factor = if samples.is_positive() {
                         samples.recip() * &coalitions_of_size * &significant_factor[size]
                     } else {
@@ -621,9 +1285,14 @@ impl<'a> SamplerState<'a> {
 
             for i in 0..self.state_groups.len() {
                 let mut result = ResponsibilityResult::new(i, self.state_groups.len());
-                for size in 1..=self.state_groups.len() {
-                    result.count_by_size[size] = factors[size][i].clone()
-                        * BigInt::from(self.significant_per_weight[size][i]);
+                for ((count, factor_entry), significant) in result
+                    .count_by_size
+                    .iter_mut()
+                    .zip(&factors)
+                    .zip(&self.significant_per_weight)
+                    .skip(1)
+                {
+                    *count = factor_entry[i].clone() * BigInt::from(significant[i]);
                 }
 
                 result.compute_values(&weights[..]);
@@ -648,6 +1317,15 @@ struct SamplesProgressReporter {
 }
 
 impl SamplesProgressReporter {
+    pub fn from_target(target: SampleTarget) -> Self {
+        match target {
+            SampleTarget::Samples(samples) => Self::new_with_count(samples as u64),
+            SampleTarget::ElapsedTime(duration) => {
+                Self::new_with_duration((duration.as_millis() / 100) as u64)
+            }
+        }
+    }
+
     pub fn new_with_count(max_size: u64) -> Self {
         let size_bar = indicatif::ProgressBar::new(max_size);
         size_bar.set_style(
@@ -720,3 +1398,260 @@ impl ProgressReporter {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The counterexample 0, 2, 4 ends in the bad state 4. The states 1, 2 and 3 can all move to 4 or
+    // to the safe sink 5, but state 1 is unreachable. The players are the states 0 to 3, where
+    // state 1 is a dummy.
+    fn example_game() -> Game {
+        let successors = vec![
+            vec![2, 3],
+            vec![4, 5],
+            vec![4, 5],
+            vec![4, 5],
+            vec![4],
+            vec![5],
+        ];
+        let mut game = Game::from_successors(0, &successors, Vec::new());
+        game.bad_states = vec![4];
+        game.mark_counterexample_path(vec![0, 2, 4]);
+        game
+    }
+
+    fn calculator(game: Game, weight_type: WeightType) -> ResponsibilityCalculator {
+        let state_groups = StateGroups::individual_from_game(&game);
+        let mut calculator = ResponsibilityCalculator::new(
+            game,
+            1,
+            weight_type,
+            state_groups,
+            ResponsibilityVersion::Pessimistic,
+        );
+        calculator.set_silent(true);
+        calculator
+    }
+
+    // Returns the responsibility of every group, ordered by group
+    fn values(mut results: Vec<ResponsibilityResult>) -> Vec<BigRational> {
+        results.sort_by_key(|result| result.group_index);
+        results
+            .into_iter()
+            .map(|result| result.total_value)
+            .collect()
+    }
+
+    fn ratio(numerator: i64, denominator: i64) -> BigRational {
+        BigRational::new(numerator.into(), denominator.into())
+    }
+
+    #[test]
+    fn antichain_engine_matches_exact_engine() {
+        let exact = values(
+            calculator(example_game(), WeightType::Shapley).compute_individual_responsibility(),
+        );
+        assert_eq!(
+            exact,
+            vec![ratio(1, 6), BigRational::zero(), ratio(2, 3), ratio(1, 6)]
+        );
+        let antichain = values(
+            calculator(example_game(), WeightType::Shapley).compute_responsibility_from_antichain(),
+        );
+        assert_eq!(antichain, exact);
+    }
+
+    #[test]
+    fn symbolic_engine_matches_exact_engine() {
+        for weight_type in [WeightType::Shapley, WeightType::Banzhaf, WeightType::Count] {
+            let exact = values(
+                calculator(example_game(), weight_type.clone()).compute_individual_responsibility(),
+            );
+            let symbolic = values(
+                calculator(example_game(), weight_type.clone())
+                    .compute_responsibility_symbolically(),
+            );
+            assert_eq!(symbolic, exact, "{}", weight_type);
+        }
+    }
+
+    // Returns the responsibility of every state that is a player, ordered by state
+    fn values_by_state(mut calculator: ResponsibilityCalculator) -> Vec<(usize, BigRational)> {
+        let results = calculator.compute_individual_responsibility();
+        let mut values = results
+            .into_iter()
+            .map(|result| {
+                let state = calculator.state_groups.members(result.group_index)[0];
+                (state, result.total_value)
+            })
+            .collect::<Vec<_>>();
+        values.sort();
+        values
+    }
+
+    #[test]
+    fn pruning_keeps_the_shapley_values() {
+        let mut pruned_game = example_game();
+        let summary = pruned_game.prune();
+        assert_eq!(summary.unreachable_states, 1);
+        assert_eq!(summary.collapsed_states, 1);
+
+        let expected = values_by_state(calculator(example_game(), WeightType::Shapley))
+            .into_iter()
+            .filter(|&(state, _)| state != 1)
+            .collect::<Vec<_>>();
+        let pruned = values_by_state(calculator(pruned_game, WeightType::Shapley));
+        assert_eq!(pruned, expected);
+    }
+
+    #[test]
+    fn excluding_dummies_keeps_the_values() {
+        let weight_types = [
+            WeightType::Shapley,
+            WeightType::Banzhaf,
+            WeightType::Count,
+            WeightType::WeightedBanzhaf(ratio(1, 3)),
+        ];
+        for weight_type in weight_types {
+            let expected = values(
+                calculator(example_game(), weight_type.clone()).compute_individual_responsibility(),
+            );
+            let mut without_dummies = calculator(example_game(), weight_type.clone());
+            without_dummies.exclude_dummy_groups();
+            assert_eq!(without_dummies.state_groups.len(), 3);
+            let actual = values(without_dummies.compute_individual_responsibility());
+            assert_eq!(actual, expected, "{}", weight_type);
+        }
+    }
+
+    fn owen_values(labels: Vec<(&str, Vec<usize>)>) -> (Vec<BigRational>, Vec<BigRational>) {
+        let mut game = example_game();
+        game.replace_labels(
+            labels
+                .into_iter()
+                .map(|(name, states)| (name.to_string(), states))
+                .collect(),
+        );
+        let mut calculator = calculator(game, WeightType::Shapley);
+        let coalition_structure =
+            CoalitionStructure::by_labels(&calculator.game, &calculator.state_groups);
+        let results = calculator.compute_owen_values(&coalition_structure);
+        (coalition_structure.union_values(&results), values(results))
+    }
+
+    #[test]
+    fn owen_value_splits_the_value_of_each_union() {
+        // Both unions win on their own, so they share the responsibility equally
+        let (union_values, owen) = owen_values(vec![("x", vec![0, 3]), ("y", vec![1, 2])]);
+        assert_eq!(union_values, vec![ratio(1, 2), ratio(1, 2)]);
+        assert_eq!(
+            owen,
+            vec![ratio(1, 4), BigRational::zero(), ratio(1, 2), ratio(1, 4)]
+        );
+
+        // With one union per state, the Owen value is the Shapley value
+        let (_, owen) = owen_values(vec![
+            ("a", vec![0]),
+            ("b", vec![1]),
+            ("c", vec![2]),
+            ("d", vec![3]),
+        ]);
+        let shapley = values(
+            calculator(example_game(), WeightType::Shapley).compute_individual_responsibility(),
+        );
+        assert_eq!(owen, shapley);
+    }
+
+    #[test]
+    fn permutation_estimates_are_reproducible_for_a_seed() {
+        let estimate = || {
+            let mut calculator = calculator(example_game(), WeightType::Shapley);
+            calculator.set_seed(42);
+            values(calculator.sample_individual_responsibilities(
+                SampleTarget::Samples(1000),
+                Estimator::Permutation,
+            ))
+        };
+        let first = estimate();
+        assert_eq!(first, estimate());
+        // Every permutation has exactly one group that makes the coalition winning
+        assert_eq!(first.iter().sum::<BigRational>(), BigRational::one());
+        assert!(first[1].is_zero());
+    }
+
+    #[test]
+    fn estimates_are_independent_of_the_thread_count() {
+        let estimate = |estimator: Estimator, thread_count: usize| {
+            let game = example_game();
+            let state_groups = StateGroups::individual_from_game(&game);
+            let mut calculator = ResponsibilityCalculator::new(
+                game,
+                thread_count,
+                WeightType::Shapley,
+                state_groups,
+                ResponsibilityVersion::Pessimistic,
+            );
+            calculator.set_silent(true);
+            calculator.set_seed(42);
+            values(
+                calculator
+                    .sample_individual_responsibilities(SampleTarget::Samples(1000), estimator),
+            )
+        };
+        for estimator in [Estimator::Coalition, Estimator::Permutation] {
+            assert_eq!(
+                estimate(estimator, 1),
+                estimate(estimator, 4),
+                "{}",
+                estimator
+            );
+        }
+    }
+
+    #[test]
+    fn power_indices_match_hand_computed_values() {
+        // The minimal winning coalitions are {2} and {0, 3}
+        let cases = [
+            (
+                WeightType::DeeganPackel,
+                vec![ratio(1, 4), BigRational::zero(), ratio(1, 2), ratio(1, 4)],
+            ),
+            (
+                WeightType::HollerPackel,
+                vec![ratio(1, 3), BigRational::zero(), ratio(1, 3), ratio(1, 3)],
+            ),
+            // The vulnerable coalitions are {2}, {0, 2}, {2, 3} and {0, 3}, each with or without the
+            // dummy, and only {0, 3} has two critical groups
+            (
+                WeightType::Johnston,
+                vec![ratio(1, 8), BigRational::zero(), ratio(3, 4), ratio(1, 8)],
+            ),
+        ];
+        for (weight_type, expected) in cases {
+            let index = values(
+                calculator(example_game(), weight_type.clone()).compute_individual_responsibility(),
+            );
+            assert_eq!(index, expected, "{}", weight_type);
+            assert_eq!(
+                index.iter().sum::<BigRational>(),
+                BigRational::one(),
+                "{}",
+                weight_type
+            );
+        }
+    }
+
+    #[test]
+    fn cheapest_fixes_are_unchanged_by_excluding_dummies() {
+        let mut all_groups = calculator(example_game(), WeightType::Shapley);
+        let costs = vec![BigRational::one(); all_groups.state_groups.len()];
+        let expected = all_groups.cheapest_fixes(&costs).unwrap();
+        assert_eq!(expected, (BigRational::one(), vec![vec![2]]));
+
+        let mut without_dummies = calculator(example_game(), WeightType::Shapley);
+        assert_eq!(without_dummies.exclude_dummy_groups(), vec![1]);
+        let costs = vec![BigRational::one(); without_dummies.state_groups.len()];
+        assert_eq!(without_dummies.cheapest_fixes(&costs).unwrap(), expected);
+    }
+}
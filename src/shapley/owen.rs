@@ -0,0 +1,181 @@
+use crate::game::Game;
+use crate::shapley::game_solving::{CachedGameSolver, StateGroups};
+use crate::shapley::{ResponsibilityResult, WeightType};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::Zero;
+use rayon::prelude::*;
+use std::collections::BTreeMap;
+
+// A partition of the state groups into unions, e.g. the components of a system. The Owen value
+// distributes responsibility first among the unions and then among the members of each union.
+pub struct CoalitionStructure {
+    unions: Vec<Union>,
+}
+
+pub struct Union {
+    pub name: String,
+    pub members: Vec<usize>, // Indices into the state groups
+}
+
+impl CoalitionStructure {
+    // State groups with the same set of labels form a union. If labels are nested (e.g. a
+    // subsystem label and a component label), this gives the finest unions, i.e. the components.
+    // Custom groups may leave states without labels, which then form a union of their own.
+    pub fn by_labels(game: &Game, state_groups: &StateGroups) -> Self {
+        let mut labels_of_state = vec![Vec::new(); game.states.len()];
+        for (label_index, label) in game.labels.iter().enumerate() {
+            for &state in &label.states {
+                labels_of_state[state].push(label_index);
+            }
+        }
+
+        let mut unions = BTreeMap::new();
+        for group in 0..state_groups.len() {
+            let mut labels = state_groups
+                .members(group)
+                .iter()
+                .flat_map(|&state| labels_of_state[state].iter().copied())
+                .collect::<Vec<_>>();
+            labels.sort();
+            labels.dedup();
+            unions.entry(labels).or_insert_with(Vec::new).push(group);
+        }
+
+        let unions = unions
+            .into_iter()
+            .map(|(labels, members)| Union {
+                name: if labels.is_empty() {
+                    "ungrouped".to_string()
+                } else {
+                    labels
+                        .iter()
+                        .map(|&label| game.labels[label].name.as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                },
+                members,
+            })
+            .collect();
+        Self { unions }
+    }
+
+    pub fn unions(&self) -> &[Union] {
+        &self.unions
+    }
+
+    // The value of a union is the sum of the values of its members, which is the value of the
+    // union in the game that is played between the unions
+    pub fn union_values(&self, results: &[ResponsibilityResult]) -> Vec<BigRational> {
+        let mut values = vec![BigRational::zero(); self.unions.len()];
+        for result in results {
+            let union = self
+                .unions
+                .iter()
+                .position(|union| union.members.contains(&result.group_index))
+                .expect("Every state group must be part of a union");
+            values[union] += &result.total_value;
+        }
+        values
+    }
+
+    // For a state group i in union B, the Owen value is the sum over all sets R of other unions and
+    // all subsets T of B \ {i} in which i is critical for the coalition (union of R) + T + i of
+    //     weight_m(|R| + 1) * weight_b(|T| + 1),
+    // where m is the number of unions, b = |B|, and weight_x are the weights of the metric for x
+    // players. For Shapley weights, this is the Owen value.
+    pub fn compute_values(
+        &self,
+        game_solver: &CachedGameSolver,
+        weight_type: &WeightType,
+    ) -> Vec<ResponsibilityResult> {
+        let n = game_solver.state_groups.len();
+        let m = self.unions.len();
+        let union_masks = self
+            .unions
+            .iter()
+            .map(|union| {
+                union
+                    .members
+                    .iter()
+                    .fold(0u64, |mask, &member| mask | 1 << member)
+            })
+            .collect::<Vec<_>>();
+        let union_weights = ResponsibilityResult::compute_weights(weight_type, m);
+
+        let mut results = (0..n)
+            .map(|group| ResponsibilityResult::new(group, n))
+            .collect::<Vec<_>>();
+        for (union_index, union) in self.unions.iter().enumerate() {
+            let b = union.members.len();
+            let member_weights = ResponsibilityResult::compute_weights(weight_type, b);
+            let other_unions = (0..m).filter(|&u| u != union_index).collect::<Vec<_>>();
+
+            // critical[member][r][t] counts the sets of r other unions and t other members of
+            // the union for which the member is critical
+            let critical = (0..1u64 << other_unions.len())
+                .into_par_iter()
+                .fold(
+                    || vec![vec![vec![0u64; b]; m]; b],
+                    |mut critical, other_union_mask| {
+                        let outside = other_unions
+                            .iter()
+                            .enumerate()
+                            .filter(|(i, _)| other_union_mask & 1 << i != 0)
+                            .fold(0u64, |mask, (_, &u)| mask | union_masks[u]);
+                        let r = other_union_mask.count_ones() as usize;
+                        for (member_index, &member) in union.members.iter().enumerate() {
+                            let others = union
+                                .members
+                                .iter()
+                                .filter(|&&other| other != member)
+                                .collect::<Vec<_>>();
+                            for inside_mask in 0..1u64 << others.len() {
+                                let inside = others
+                                    .iter()
+                                    .enumerate()
+                                    .filter(|(i, _)| inside_mask & 1 << i != 0)
+                                    .fold(0u64, |mask, (_, &&other)| mask | 1 << other);
+                                let coalition = outside | inside;
+                                if game_solver.is_game_winning(coalition | 1 << member)
+                                    && !game_solver.is_game_winning(coalition)
+                                {
+                                    let t = inside_mask.count_ones() as usize;
+                                    critical[member_index][r][t] += 1;
+                                }
+                            }
+                        }
+                        critical
+                    },
+                )
+                .reduce(
+                    || vec![vec![vec![0u64; b]; m]; b],
+                    |mut x, y| {
+                        for (x, y) in x.iter_mut().flatten().zip(y.iter().flatten()) {
+                            for (x, y) in x.iter_mut().zip(y.iter()) {
+                                *x += y;
+                            }
+                        }
+                        x
+                    },
+                );
+
+            // The weights depend on two sizes, so only the total value is stored
+            for (member_index, &member) in union.members.iter().enumerate() {
+                let result = &mut results[member];
+                for (r, counts) in critical[member_index].iter().enumerate() {
+                    for (t, &count) in counts.iter().enumerate() {
+                        if count == 0 {
+                            continue;
+                        }
+                        let value = BigRational::from_integer(BigInt::from(count))
+                            * &union_weights[r + 1]
+                            * &member_weights[t + 1];
+                        result.total_value += &value;
+                    }
+                }
+            }
+        }
+        results
+    }
+}
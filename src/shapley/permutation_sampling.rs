@@ -0,0 +1,124 @@
+use crate::game::{Game, Player};
+use crate::shapley::{
+    ResponsibilityResult, SampleChunks, SampleTarget, SamplesProgressReporter, StateGroups,
+    WeightType,
+};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::One;
+
+// Estimates responsibility by walking random orders of the state groups. Groups are added to the
+// coalition one after another until the coalition wins; the group that caused the switch is the
+// pivot of that order. As the game is monotone, every order has at most one pivot, so the walk can
+// stop as soon as it is found.
+pub struct PermutationSamplerState<'a> {
+    game: Game,
+    state_groups: &'a StateGroups,
+    total_samples: usize,
+    pivots_by_size: Vec<Vec<usize>>, // Indexed by group and then by the size of the coalition that includes the pivot
+    progress_bar: Option<SamplesProgressReporter>,
+}
+
+impl<'a> PermutationSamplerState<'a> {
+    pub fn new(game: Game, state_groups: &'a StateGroups) -> PermutationSamplerState<'a> {
+        let size = state_groups.len();
+        Self {
+            game,
+            state_groups,
+            total_samples: 0,
+            pivots_by_size: vec![vec![0; size + 1]; size],
+            progress_bar: None,
+        }
+    }
+
+    pub fn add_progress_bar(&mut self, target: SampleTarget) {
+        self.progress_bar = Some(SamplesProgressReporter::from_target(target));
+    }
+
+    pub fn total_samples(&self) -> usize {
+        self.total_samples
+    }
+
+    pub fn run(&mut self, chunks: &SampleChunks) {
+        while let Some(mut chunk) = chunks.next_chunk() {
+            if let Some(progress_bar) = &mut self.progress_bar {
+                progress_bar.set_current_size(chunks.progress(&chunk));
+            }
+            for _ in 0..chunk.samples {
+                // Every order is drawn from scratch so that it only depends on the chunk's generator
+                let mut order = (0..self.state_groups.len()).collect::<Vec<_>>();
+                chunk.rng.shuffle(&mut order);
+                self.sample(&order);
+            }
+        }
+        if let Some(progress_bar) = &mut self.progress_bar {
+            progress_bar.finish();
+            println!("Waiting for other processes to finish");
+        }
+    }
+
+    pub fn sample(&mut self, order: &[usize]) {
+        let mut added = 0;
+        for &group in order {
+            self.state_groups.add_to_coalition(&mut self.game, group);
+            added += 1;
+            if self.game.determine_winner() == Player::Safe {
+                self.pivots_by_size[group][added] += 1;
+                break;
+            }
+        }
+
+        for &group in &order[..added] {
+            self.state_groups
+                .remove_from_coalition(&mut self.game, group);
+        }
+
+        self.total_samples += 1;
+    }
+
+    pub fn add_values(&mut self, other: &PermutationSamplerState) {
+        self.total_samples += other.total_samples;
+        for (own_pivots, other_pivots) in self
+            .pivots_by_size
+            .iter_mut()
+            .zip(other.pivots_by_size.iter())
+        {
+            for (own, other) in own_pivots.iter_mut().zip(other_pivots.iter()) {
+                *own += other;
+            }
+        }
+    }
+
+    pub fn to_responsibility_results(&self, weight_type: &WeightType) -> Vec<ResponsibilityResult> {
+        let n = self.state_groups.len();
+
+        // In a uniformly random order, a group ends up at position k with probability 1/n, and the
+        // groups before it are then a uniformly random coalition of size k-1. The number of
+        // coalitions of size k in which the group is critical is therefore estimated by
+        //     pivots_at_k / total_samples * n * binomial(n-1, k-1)
+        let mut binomials = Vec::with_capacity(n);
+        let mut current_value = BigInt::one();
+        binomials.push(current_value.clone());
+        for k in 1..n {
+            current_value = current_value * (n - k) / k;
+            binomials.push(current_value.clone());
+        }
+
+        let weights = ResponsibilityResult::compute_weights(weight_type, n);
+        let mut results = Vec::with_capacity(n);
+        for (group, pivots) in self.pivots_by_size.iter().enumerate() {
+            let mut result = ResponsibilityResult::new(group, n);
+            if self.total_samples > 0 {
+                for size in 1..=n {
+                    result.count_by_size[size] = BigRational::new(
+                        BigInt::from(pivots[size]) * n * &binomials[size - 1],
+                        self.total_samples.into(),
+                    );
+                }
+            }
+            result.compute_values(&weights[..]);
+            results.push(result);
+        }
+        results
+    }
+}
@@ -0,0 +1,169 @@
+use crate::shapley::game_solving::CachedGameSolver;
+use crate::shapley::{ProgressReporter, WeightType};
+use num_bigint::BigInt;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
+use rayon::prelude::*;
+
+// Power indices that are not defined by a weight per coalition size, but over the minimal winning
+// coalitions (Deegan-Packel, Holler-Packel) or over the critical groups of each winning coalition
+// (Johnston). To report them like the other metrics, they are still split by coalition size:
+// `counts[i][k]` is the (possibly fractional) contribution of the coalitions of size k to group i,
+// and every size has the same weight, which normalises the index so that it sums up to one.
+pub struct PowerIndexCounts {
+    pub counts: Vec<Vec<BigRational>>,
+    pub weights: Vec<BigRational>,
+}
+
+impl PowerIndexCounts {
+    pub fn compute(
+        weight_type: &WeightType,
+        game_solver: &CachedGameSolver,
+        thread_count: usize,
+        step_size: usize,
+        silent: bool,
+    ) -> Self {
+        let n = game_solver.state_groups.len();
+        match weight_type {
+            WeightType::DeeganPackel | WeightType::HollerPackel => {
+                Self::from_minimal_coalitions(weight_type, game_solver.minimal_coalitions(), n)
+            }
+            WeightType::Johnston => {
+                Self::from_vulnerable_coalitions(game_solver, thread_count, step_size, silent)
+            }
+            _ => panic!("{} is not a power index", weight_type),
+        }
+    }
+
+    // Deegan-Packel: every minimal winning coalition is equally likely to form and splits its
+    // payoff equally among its members, i.e. DP(i) = 1/|M| * sum over S in M with i in S of 1/|S|.
+    // Holler-Packel: every member of a minimal winning coalition receives the full payoff, which is
+    // normalised by the total number of memberships.
+    fn from_minimal_coalitions(
+        weight_type: &WeightType,
+        minimal_coalitions: &[u64],
+        n: usize,
+    ) -> Self {
+        let mut counts = vec![vec![BigRational::zero(); n + 1]; n];
+        let mut memberships = 0usize;
+        for &coalition in minimal_coalitions {
+            let size = coalition.count_ones() as usize;
+            memberships += size;
+            let share = if *weight_type == WeightType::DeeganPackel {
+                BigRational::new(BigInt::one(), size.into())
+            } else {
+                BigRational::one()
+            };
+            for (group, group_counts) in counts.iter_mut().enumerate() {
+                if coalition & 1 << group != 0 {
+                    group_counts[size] += &share;
+                }
+            }
+        }
+
+        let normaliser = if *weight_type == WeightType::DeeganPackel {
+            minimal_coalitions.len()
+        } else {
+            memberships
+        };
+        Self {
+            counts,
+            weights: uniform_weights(normaliser.into(), n),
+        }
+    }
+
+    // Johnston: every winning coalition with at least one critical group (a vulnerable coalition)
+    // splits its payoff equally among its critical groups. Counts the critical groups of all
+    // winning coalitions, which requires enumerating all coalitions.
+    fn from_vulnerable_coalitions(
+        game_solver: &CachedGameSolver,
+        thread_count: usize,
+        step_size: usize,
+        silent: bool,
+    ) -> Self {
+        let n = game_solver.state_groups.len();
+        let coalition_count = 1u64 << n;
+        let coalitions = std::sync::Mutex::new((0..coalition_count).step_by(step_size));
+        let progress_reporter =
+            std::sync::Mutex::new(ProgressReporter::new(coalition_count, silent));
+
+        // tallies[(group * (n + 1) + size) * (n + 1) + critical_count] is the number of vulnerable
+        // coalitions of the given size with the given number of critical groups, in which the
+        // group is critical
+        let tally_index = |group: usize, size: usize, critical_count: usize| {
+            (group * (n + 1) + size) * (n + 1) + critical_count
+        };
+        let mut thread_tallies = vec![(vec![0u64; n * (n + 1) * (n + 1)], 0u64); thread_count];
+        thread_tallies.par_iter_mut().enumerate().for_each(
+            |(thread_index, (tallies, vulnerable_count))| loop {
+                let first_coalition = match coalitions.lock().unwrap().next() {
+                    None => break,
+                    Some(first_coalition) => first_coalition,
+                };
+                for coalition in
+                    first_coalition..(first_coalition + step_size as u64).min(coalition_count)
+                {
+                    if !game_solver.is_game_winning(coalition) {
+                        continue;
+                    }
+                    let critical_groups = (0..n)
+                        .filter(|&group| {
+                            coalition & 1 << group != 0
+                                && !game_solver.is_game_winning(coalition & !(1 << group))
+                        })
+                        .collect::<Vec<_>>();
+                    if critical_groups.is_empty() {
+                        continue;
+                    }
+                    *vulnerable_count += 1;
+                    let size = coalition.count_ones() as usize;
+                    for &group in &critical_groups {
+                        tallies[tally_index(group, size, critical_groups.len())] += 1;
+                    }
+                }
+
+                if thread_index == 0 {
+                    progress_reporter
+                        .lock()
+                        .unwrap()
+                        .set_current_coalition(first_coalition);
+                }
+            },
+        );
+        progress_reporter.lock().unwrap().set_finished();
+
+        let mut counts = vec![vec![BigRational::zero(); n + 1]; n];
+        let mut vulnerable_count = 0u64;
+        for (tallies, thread_vulnerable_count) in &thread_tallies {
+            vulnerable_count += thread_vulnerable_count;
+            for (group, group_counts) in counts.iter_mut().enumerate() {
+                for (size, count) in group_counts.iter_mut().enumerate() {
+                    for critical_count in 1..=n {
+                        let tally = tallies[tally_index(group, size, critical_count)];
+                        if tally != 0 {
+                            *count += BigRational::new(tally.into(), critical_count.into());
+                        }
+                    }
+                }
+            }
+        }
+
+        Self {
+            counts,
+            weights: uniform_weights(vulnerable_count.into(), n),
+        }
+    }
+}
+
+// Returns weights that are 1/normaliser for every coalition size, or zero if there is nothing to
+// normalise by (i.e. there are no winning coalitions).
+pub fn uniform_weights(normaliser: BigInt, n: usize) -> Vec<BigRational> {
+    let weight = if normaliser.is_zero() {
+        BigRational::zero()
+    } else {
+        BigRational::new(BigInt::one(), normaliser)
+    };
+    let mut weights = vec![weight; n + 1];
+    weights[0] = BigRational::zero();
+    weights
+}
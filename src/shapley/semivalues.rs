@@ -0,0 +1,33 @@
+use num_rational::BigRational;
+use num_traits::{One, Pow};
+
+// Weights of parametric semivalues. A semivalue weighs a coalition of size k in which a group is
+// critical by the probability that exactly the k-1 other members of the coalition join it, where
+// every other group joins independently with a probability t drawn from some distribution.
+
+// Beta Shapley draws t from a Beta(beta, alpha) distribution, which gives the weight
+//     B(k-1 + beta, n-k + alpha) / B(alpha, beta)
+//   = beta^(k-1) * alpha^(n-k) / (alpha + beta)^(n-1)
+// for coalitions of size k, where x^(m) denotes the rising factorial. It is therefore rational for
+// rational parameters. alpha = beta = 1 is the Shapley value, and alpha > beta emphasises small
+// coalitions.
+pub fn beta_weight(alpha: &BigRational, beta: &BigRational, k: usize, n: usize) -> BigRational {
+    rising_factorial(beta, k - 1) * rising_factorial(alpha, n - k)
+        / rising_factorial(&(alpha + beta), n - 1)
+}
+
+// Weighted Banzhaf fixes t = p, which gives the weight p^(k-1) * (1-p)^(n-k) for coalitions of
+// size k. p = 1/2 is the Banzhaf value.
+pub fn weighted_banzhaf_weight(p: &BigRational, k: usize, n: usize) -> BigRational {
+    Pow::pow(p, k - 1) * Pow::pow(BigRational::one() - p, n - k)
+}
+
+fn rising_factorial(x: &BigRational, m: usize) -> BigRational {
+    let mut result = BigRational::one();
+    let mut factor = x.clone();
+    for _ in 0..m {
+        result *= &factor;
+        factor += BigRational::one();
+    }
+    result
+}
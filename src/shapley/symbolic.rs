@@ -0,0 +1,96 @@
+use crate::bdd::{Bdd, BddNode, FALSE};
+use crate::game::Game;
+use crate::shapley::StateGroups;
+use num_bigint::BigInt;
+
+// Represents the winning coalitions as a BDD with one variable per state group, so that the number
+// of critical coalitions can be obtained by counting models instead of enumerating coalitions.
+pub struct SymbolicCounter {
+    n: usize,
+    bdd: Bdd,
+    winning: BddNode,
+    group_variables: Vec<usize>,
+}
+
+impl SymbolicCounter {
+    pub fn from_game(game: &Game, state_groups: &StateGroups) -> Self {
+        let n = state_groups.len();
+        let mut bdd = Bdd::new();
+
+        let group_variables = Self::order_groups(game, state_groups);
+        let mut coalition_membership = vec![FALSE; game.states.len()];
+        for (group, &group_variable) in group_variables.iter().enumerate() {
+            let variable = bdd.variable(group_variable);
+            for &member in state_groups.members(group) {
+                coalition_membership[member] = bdd.or(coalition_membership[member], variable);
+            }
+        }
+
+        let losing = game.compute_reach_winning_coalitions(&mut bdd, &coalition_membership);
+        let winning = bdd.not(losing);
+        Self {
+            n,
+            bdd,
+            winning,
+            group_variables,
+        }
+    }
+
+    // The size of the BDD depends heavily on the variable order. Ordering the groups by when a
+    // breadth-first search from the initial state first reaches one of their members keeps groups
+    // that influence each other close together, which worked far better on the experiments than
+    // the order of the state indices or the distance to the bad states.
+    fn order_groups(game: &Game, state_groups: &StateGroups) -> Vec<usize> {
+        let positions = game.breadth_first_positions();
+        let mut groups = (0..state_groups.len()).collect::<Vec<_>>();
+        groups.sort_by_key(|&group| {
+            state_groups
+                .members(group)
+                .iter()
+                .filter_map(|&member| positions[member])
+                .min()
+                .unwrap_or(usize::MAX)
+        });
+
+        let mut group_variables = vec![0; groups.len()];
+        for (variable, &group) in groups.iter().enumerate() {
+            group_variables[group] = variable;
+        }
+        group_variables
+    }
+
+    pub fn node_count(&self) -> usize {
+        self.bdd.size(self.winning)
+    }
+
+    // Returns, for every group, the number of coalitions of each size (including the group) in
+    // which the group is critical. Group i is critical for S ∪ {i} iff the winning function with i
+    // set to true holds for S and the one with i set to false does not. Groups for which
+    // `is_needed` returns false are skipped and get a count of zero.
+    pub fn critical_counts(&mut self, is_needed: impl Fn(usize) -> bool) -> Vec<Vec<BigInt>> {
+        let mut counts = Vec::with_capacity(self.n);
+        for group in 0..self.n {
+            if !is_needed(group) {
+                counts.push(vec![BigInt::from(0); self.n + 1]);
+                continue;
+            }
+            let variable = self.group_variables[group];
+            let with_group = self.bdd.restrict(self.winning, variable, true);
+            let without_group = self.bdd.restrict(self.winning, variable, false);
+            let not_without_group = self.bdd.not(without_group);
+            let critical = self.bdd.and(with_group, not_without_group);
+
+            let mut group_counts = vec![BigInt::from(0); self.n + 1];
+            for (others, count) in self
+                .bdd
+                .count_by_size(critical, self.n, Some(variable))
+                .into_iter()
+                .enumerate()
+            {
+                group_counts[others + 1] = count;
+            }
+            counts.push(group_counts);
+        }
+        counts
+    }
+}
@@ -0,0 +1,291 @@
+use crate::game::{Game, Player};
+use crate::shapley::StateGroups;
+use std::collections::BTreeMap;
+
+const TRANSITION: u8 = 0;
+const PATH_TRANSITION: u8 = 1;
+const MEMBERSHIP: u8 = 2;
+
+type Coloring = Vec<usize>;
+type Signature = (usize, Vec<(u8, usize)>, Vec<(u8, usize)>);
+
+// Finds state groups that are interchangeable, i.e. that are mapped onto each other by an
+// automorphism of the game. Such an automorphism maps every coalition to one that wins iff the
+// original coalition does, so symmetric groups have identical responsibility.
+//
+// The game is viewed as a coloured graph with one vertex per state and one vertex per group, which
+// is connected to its members. Automorphisms must preserve the owner of each state, the bad states,
+// the initial state and which transitions are on the counterexample. Candidates are found by colour
+// refinement and then confirmed by searching for an explicit automorphism.
+pub struct SymmetryDetector {
+    group_offset: usize,
+    group_count: usize,
+    initial_colors: Coloring,
+    out_edges: Vec<Vec<(u8, usize)>>,
+    in_edges: Vec<Vec<(u8, usize)>>,
+}
+
+impl SymmetryDetector {
+    pub fn new(game: &Game, state_groups: &StateGroups) -> Self {
+        let group_offset = game.states.len();
+        let group_count = state_groups.len();
+        let vertex_count = group_offset + group_count;
+
+        let mut out_edges = vec![Vec::new(); vertex_count];
+        let mut in_edges = vec![Vec::new(); vertex_count];
+        for (source, target, on_path) in game.transitions() {
+            let label = if on_path { PATH_TRANSITION } else { TRANSITION };
+            out_edges[source].push((label, target));
+            in_edges[target].push((label, source));
+        }
+        for group in 0..group_count {
+            for &member in state_groups.members(group) {
+                out_edges[group_offset + group].push((MEMBERSHIP, member));
+                in_edges[member].push((MEMBERSHIP, group_offset + group));
+            }
+        }
+
+        let mut initial_colors = Vec::with_capacity(vertex_count);
+        for (index, state) in game.states.iter().enumerate() {
+            let owner = match state.default_owner {
+                Player::Reach => 0,
+                Player::Safe => 1,
+                Player::Path => 2,
+            };
+            let is_bad = game.objective_states().contains(&index) as usize;
+            let is_initial = (index == game.initial_state) as usize;
+            initial_colors.push(1 + owner * 4 + is_bad * 2 + is_initial);
+        }
+        initial_colors.resize(vertex_count, 0);
+
+        Self {
+            group_offset,
+            group_count,
+            initial_colors,
+            out_edges,
+            in_edges,
+        }
+    }
+
+    // Returns, for every group, the smallest group that it is symmetric to
+    pub fn find_representatives(&self) -> Vec<usize> {
+        let mut stable = [self.initial_colors.clone()];
+        self.refine(&mut stable);
+        let [stable] = stable;
+
+        // Union-find over the groups, where the root of each orbit is its smallest group
+        let mut orbits = (0..self.group_count).collect::<Vec<_>>();
+        for group in 0..self.group_count {
+            if Self::find_root(&mut orbits, group) != group {
+                continue;
+            }
+            // Only earlier groups that are still roots need to be tried, as all others are
+            // already known to be symmetric to one of them
+            for candidate in 0..group {
+                if Self::find_root(&mut orbits, candidate) != candidate
+                    || stable[self.group_offset + candidate] != stable[self.group_offset + group]
+                {
+                    continue;
+                }
+                let fresh_color = self.initial_colors.len();
+                let mut left = stable.clone();
+                let mut right = stable.clone();
+                left[self.group_offset + candidate] = fresh_color;
+                right[self.group_offset + group] = fresh_color;
+                if let Some(automorphism) = self.find_automorphism(left, right) {
+                    // Every group lies in the same orbit as its image
+                    for other in 0..self.group_count {
+                        let image = automorphism[self.group_offset + other] - self.group_offset;
+                        let (other_root, image_root) = (
+                            Self::find_root(&mut orbits, other),
+                            Self::find_root(&mut orbits, image),
+                        );
+                        orbits[other_root.max(image_root)] = other_root.min(image_root);
+                    }
+                    break;
+                }
+            }
+        }
+        (0..self.group_count)
+            .map(|group| Self::find_root(&mut orbits, group))
+            .collect()
+    }
+
+    fn find_root(orbits: &mut [usize], mut group: usize) -> usize {
+        while orbits[group] != group {
+            orbits[group] = orbits[orbits[group]];
+            group = orbits[group];
+        }
+        group
+    }
+
+    // Searches for an automorphism that maps every vertex to a vertex of the same colour in the
+    // other colouring, individualising one vertex at a time until the colourings are discrete.
+    fn find_automorphism(&self, left: Coloring, right: Coloring) -> Option<Vec<usize>> {
+        let mut colorings = [left, right];
+        if !self.refine(&mut colorings) {
+            return None;
+        }
+        let [left, right] = colorings;
+
+        // Models often contain many interchangeable states (e.g. states with the same predecessor
+        // and successor) that refinement cannot tell apart, and individualising them one by one is
+        // slow. Pairing the vertices of each colour in the order of their indices usually already
+        // gives an automorphism, so this is tried first.
+        let permutation = Self::pair_by_color(&left, &right);
+        if self.is_automorphism(&permutation) {
+            return Some(permutation);
+        }
+
+        let mut color_counts = vec![0; left.iter().max().map_or(0, |&c| c + 1)];
+        for &color in &left {
+            color_counts[color] += 1;
+        }
+        // If the colourings are discrete, the pairing was the only candidate
+        let split_color = (0..color_counts.len()).find(|&c| color_counts[c] > 1)?;
+
+        let fresh_color = color_counts.len();
+        let left_vertex = left.iter().position(|&c| c == split_color).unwrap();
+        for right_vertex in (0..right.len()).filter(|&v| right[v] == split_color) {
+            let mut individualised_left = left.clone();
+            let mut individualised_right = right.clone();
+            individualised_left[left_vertex] = fresh_color;
+            individualised_right[right_vertex] = fresh_color;
+            if let Some(automorphism) =
+                self.find_automorphism(individualised_left, individualised_right)
+            {
+                return Some(automorphism);
+            }
+        }
+        None
+    }
+
+    // Refines all colourings simultaneously until they are stable. Colours are assigned by the
+    // sorted signatures, so that vertices with the same colour in different colourings remain
+    // comparable. Returns false if the colourings end up with different colour frequencies, in
+    // which case no automorphism maps one onto the other.
+    fn refine(&self, colorings: &mut [Coloring]) -> bool {
+        loop {
+            let color_count_before = Self::color_count(&colorings[0]);
+            let signatures = colorings
+                .iter()
+                .map(|coloring| {
+                    (0..coloring.len())
+                        .map(|vertex| self.signature(coloring, vertex))
+                        .collect::<Vec<_>>()
+                })
+                .collect::<Vec<_>>();
+
+            let mut new_colors: BTreeMap<&Signature, usize> = BTreeMap::new();
+            for signature in signatures.iter().flatten() {
+                new_colors.insert(signature, 0);
+            }
+            for (color, value) in new_colors.values_mut().enumerate() {
+                *value = color;
+            }
+            for (coloring, signatures) in colorings.iter_mut().zip(signatures.iter()) {
+                for (color, signature) in coloring.iter_mut().zip(signatures.iter()) {
+                    *color = new_colors[signature];
+                }
+            }
+
+            let frequencies = Self::color_frequencies(&colorings[0]);
+            if colorings[1..]
+                .iter()
+                .any(|coloring| Self::color_frequencies(coloring) != frequencies)
+            {
+                return false;
+            }
+            if Self::color_count(&colorings[0]) == color_count_before {
+                return true;
+            }
+        }
+    }
+
+    // Maps the k-th vertex of each colour in the left colouring to the k-th vertex of that colour in
+    // the right colouring. Both colourings must have the same colour frequencies.
+    fn pair_by_color(left: &[usize], right: &[usize]) -> Vec<usize> {
+        let mut vertices_of_color = vec![Vec::new(); right.iter().max().map_or(0, |&c| c + 1)];
+        for (vertex, &color) in right.iter().enumerate().rev() {
+            vertices_of_color[color].push(vertex);
+        }
+        left.iter()
+            .map(|&color| vertices_of_color[color].pop().unwrap())
+            .collect()
+    }
+
+    fn signature(&self, coloring: &[usize], vertex: usize) -> Signature {
+        let neighbour_colors = |edges: &[(u8, usize)]| {
+            let mut colors = edges
+                .iter()
+                .map(|&(label, neighbour)| (label, coloring[neighbour]))
+                .collect::<Vec<_>>();
+            colors.sort_unstable();
+            colors
+        };
+        (
+            coloring[vertex],
+            neighbour_colors(&self.out_edges[vertex]),
+            neighbour_colors(&self.in_edges[vertex]),
+        )
+    }
+
+    fn color_count(coloring: &[usize]) -> usize {
+        Self::color_frequencies(coloring).len()
+    }
+
+    fn color_frequencies(coloring: &[usize]) -> BTreeMap<usize, usize> {
+        let mut frequencies = BTreeMap::new();
+        for &color in coloring {
+            *frequencies.entry(color).or_insert(0) += 1;
+        }
+        frequencies
+    }
+
+    fn is_automorphism(&self, permutation: &[usize]) -> bool {
+        (0..permutation.len()).all(|vertex| {
+            let image = permutation[vertex];
+            if self.initial_colors[vertex] != self.initial_colors[image] {
+                return false;
+            }
+            let mut mapped_edges = self.out_edges[vertex]
+                .iter()
+                .map(|&(label, target)| (label, permutation[target]))
+                .collect::<Vec<_>>();
+            let mut image_edges = self.out_edges[image].clone();
+            mapped_edges.sort_unstable();
+            image_edges.sort_unstable();
+            mapped_edges == image_edges
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // The initial state 0 moves to 1 or 2, which both move to the bad state 3 or the safe sink 4
+    fn diamond_game() -> Game {
+        let successors = vec![vec![1, 2], vec![3, 4], vec![3, 4], vec![3], vec![4]];
+        let mut game = Game::from_successors(0, &successors, Vec::new());
+        game.bad_states = vec![3];
+        game
+    }
+
+    #[test]
+    fn finds_interchangeable_states() {
+        let game = diamond_game();
+        let state_groups = StateGroups::individual_from_game(&game);
+        let representatives = SymmetryDetector::new(&game, &state_groups).find_representatives();
+        assert_eq!(representatives, vec![0, 1, 1]);
+    }
+
+    #[test]
+    fn counterexample_breaks_symmetry() {
+        let mut game = diamond_game();
+        game.mark_counterexample_path(vec![0, 1, 3]);
+        let state_groups = StateGroups::individual_from_game(&game);
+        let representatives = SymmetryDetector::new(&game, &state_groups).find_representatives();
+        assert_eq!(representatives, vec![0, 1, 2]);
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::{BTreeMap, VecDeque};
+
+#[derive(Default)]
 pub struct TransitionSystem {
     pub states: Vec<State>,
     pub initial_state: usize,
@@ -20,6 +23,35 @@ impl TransitionSystem {
         }
     }
 
+    // Builds a transition system for tests with a single variable "s", whose value in every state
+    // is the index of the state, and the given labels. The initial state is state 0.
+    #[cfg(test)]
+    pub fn from_successors(successors: &[Vec<usize>], labels: &[(&str, Vec<usize>)]) -> Self {
+        let mut variable = Variable::new("s".to_string());
+        let mut states = successors
+            .iter()
+            .enumerate()
+            .map(|(index, targets)| {
+                let mut state =
+                    State::new(vec![variable.get_valuation_index_or_add(&index.to_string())]);
+                for &target in targets {
+                    state
+                        .outgoing_transitions
+                        .push(Transition::new(target, None));
+                }
+                state
+            })
+            .collect::<Vec<_>>();
+        let mut label_names = Vec::new();
+        for (index, (name, members)) in labels.iter().enumerate() {
+            label_names.push((index, name.to_string()));
+            for &member in members {
+                states[member].labels.push(index);
+            }
+        }
+        Self::new(states, 0, vec![variable], label_names)
+    }
+
     pub fn get_variable_index(&self, name: &str) -> Option<usize> {
         for (i, variable) in self.variables.iter().enumerate() {
             if variable.name == name {
@@ -48,8 +80,302 @@ impl TransitionSystem {
         None
     }
 
-    pub fn verify_counterexample(&self, counterexample: &Vec<usize>) {
-        if counterexample.len() == 0 {
+    // Groups the states by their values of the given variables. Returns one group per combination
+    // of values that occurs, named like the states, e.g. "turn=2, phase=1".
+    pub fn groups_by_variables(&self, variable_names: &[String]) -> Vec<(String, Vec<usize>)> {
+        let variables = variable_names
+            .iter()
+            .map(|name| {
+                self.get_variable_index(name)
+                    .unwrap_or_else(|| panic!("Could not find variable \"{}\"", name))
+            })
+            .collect::<Vec<_>>();
+
+        let mut groups = BTreeMap::new();
+        for (index, state) in self.states.iter().enumerate() {
+            let values = variables
+                .iter()
+                .map(|&variable| state.valuation_indices[variable])
+                .collect::<Vec<_>>();
+            groups.entry(values).or_insert_with(Vec::new).push(index);
+        }
+
+        groups
+            .into_iter()
+            .map(|(values, states)| {
+                let name = variables
+                    .iter()
+                    .zip(values.iter())
+                    .map(|(&variable, &value)| {
+                        let variable = &self.variables[variable];
+                        format!("{}={}", variable.name, variable.valuation_names[value])
+                    })
+                    .collect::<Vec<String>>()
+                    .join(", ");
+                (name, states)
+            })
+            .collect()
+    }
+
+    // Returns all paths of minimal length from the initial state to a bad state, but at most
+    // `limit` of them
+    pub fn shortest_counterexamples(&self, limit: usize) -> Vec<Vec<usize>> {
+        // Backward breadth-first search for the distance of every state to the bad states
+        let mut predecessors = vec![Vec::new(); self.states.len()];
+        for (source, state) in self.states.iter().enumerate() {
+            for transition in &state.outgoing_transitions {
+                predecessors[transition.destination].push(source);
+            }
+        }
+        let mut distance_to_bad = vec![None; self.states.len()];
+        let mut queue = VecDeque::new();
+        for (index, state) in self.states.iter().enumerate() {
+            if state.is_bad {
+                distance_to_bad[index] = Some(0);
+                queue.push_back(index);
+            }
+        }
+        while let Some(state) = queue.pop_front() {
+            let distance = distance_to_bad[state].unwrap();
+            for &predecessor in &predecessors[state] {
+                if distance_to_bad[predecessor].is_none() && !self.states[predecessor].is_bad {
+                    distance_to_bad[predecessor] = Some(distance + 1);
+                    queue.push_back(predecessor);
+                }
+            }
+        }
+
+        // Every shortest path only takes transitions that decrease the distance by one
+        let mut paths = Vec::new();
+        if distance_to_bad[self.initial_state].is_none() {
+            return paths;
+        }
+        let mut stack = vec![vec![self.initial_state]];
+        while let Some(path) = stack.pop() {
+            if paths.len() >= limit {
+                break;
+            }
+            let state = *path.last().unwrap();
+            let distance = distance_to_bad[state].unwrap();
+            if distance == 0 {
+                paths.push(path);
+                continue;
+            }
+            let mut successors = self.states[state]
+                .outgoing_transitions
+                .iter()
+                .map(|transition| transition.destination)
+                .filter(|&successor| distance_to_bad[successor] == Some(distance - 1))
+                .collect::<Vec<_>>();
+            successors.sort();
+            successors.dedup();
+            for &successor in successors.iter().rev() {
+                let mut extended_path = path.clone();
+                extended_path.push(successor);
+                stack.push(extended_path);
+            }
+        }
+        paths
+    }
+
+    // Shortens a counterexample to a shortest path from the initial state to its first bad state
+    // that does not visit other bad states. The play is decided once it reaches a bad state, so
+    // the rest of the counterexample does not matter. This removes loops and
+    // replaces detours by shorter subpaths. Among all shortest paths, the successor that occurs
+    // latest on the counterexample is preferred, and otherwise the one with the lowest index, so
+    // that the result is canonical and keeps as much of the counterexample as possible.
+    pub fn shorten_counterexample(&self, counterexample: &[usize]) -> Vec<usize> {
+        let first_bad = counterexample
+            .iter()
+            .position(|&state| self.states[state].is_bad)
+            .unwrap_or(counterexample.len() - 1);
+        let counterexample = &counterexample[..=first_bad];
+        let target = counterexample[first_bad];
+        let mut predecessors = vec![Vec::new(); self.states.len()];
+        for (source, state) in self.states.iter().enumerate() {
+            for transition in &state.outgoing_transitions {
+                predecessors[transition.destination].push(source);
+            }
+        }
+        let mut distance_to_target = vec![None; self.states.len()];
+        distance_to_target[target] = Some(0);
+        let mut queue = VecDeque::from([target]);
+        while let Some(state) = queue.pop_front() {
+            let distance = distance_to_target[state].unwrap();
+            for &predecessor in &predecessors[state] {
+                if distance_to_target[predecessor].is_none() && !self.states[predecessor].is_bad {
+                    distance_to_target[predecessor] = Some(distance + 1);
+                    queue.push_back(predecessor);
+                }
+            }
+        }
+
+        let mut position_on_counterexample = vec![None; self.states.len()];
+        for (position, &state) in counterexample.iter().enumerate() {
+            position_on_counterexample[state] = Some(position);
+        }
+        let mut path = vec![self.initial_state];
+        let mut state = self.initial_state;
+        while state != target {
+            let distance = distance_to_target[state].unwrap();
+            state = self.states[state]
+                .outgoing_transitions
+                .iter()
+                .map(|transition| transition.destination)
+                .filter(|&successor| distance_to_target[successor] == Some(distance - 1))
+                .max_by_key(|&successor| {
+                    (
+                        position_on_counterexample[successor],
+                        usize::MAX - successor,
+                    )
+                })
+                .unwrap();
+            path.push(state);
+        }
+        path
+    }
+
+    // Returns a random path from the initial state to a bad state without repeated states. The
+    // path is found by a depth-first search that visits the successors in random order and stops
+    // at the first bad state. Returns None if no bad state is reachable.
+    pub fn random_counterexample(&self, rng: &mut fastrand::Rng) -> Option<Vec<usize>> {
+        let mut visited = vec![false; self.states.len()];
+        let mut parent = vec![None; self.states.len()];
+        let mut stack = vec![self.initial_state];
+        while let Some(state) = stack.pop() {
+            if visited[state] {
+                continue;
+            }
+            visited[state] = true;
+            if self.states[state].is_bad {
+                // Every parent was visited before its children, so following them terminates
+                let mut path = vec![state];
+                while let Some(previous) = parent[*path.last().unwrap()] {
+                    path.push(previous);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            let mut successors = self.states[state]
+                .outgoing_transitions
+                .iter()
+                .map(|transition| transition.destination)
+                .filter(|&successor| !visited[successor])
+                .collect::<Vec<_>>();
+            rng.shuffle(&mut successors);
+            for successor in successors {
+                parent[successor] = Some(state);
+                stack.push(successor);
+            }
+        }
+        None
+    }
+
+    // Removes the bad flag from all states and returns the states that had it. The good states of
+    // a liveness property are parsed like bad states, as both are the target of PRISM's query.
+    pub fn take_bad_states(&mut self) -> Vec<usize> {
+        let mut bad_states = Vec::new();
+        for (index, state) in self.states.iter_mut().enumerate() {
+            if state.is_bad {
+                state.is_bad = false;
+                bad_states.push(index);
+            }
+        }
+        bad_states
+    }
+
+    // Returns a counterexample for the liveness property that the given good states are visited
+    // eventually (or, if `infinitely_often` is set, infinitely often). This is either a lasso, whose
+    // last state closes a cycle by repeating an earlier state, or a path into a state without
+    // successors. Among all violations, the one whose last state is discovered first by a
+    // breadth-first search is returned, which keeps the prefix short. Returns None if the property
+    // holds.
+    pub fn lasso_counterexample(
+        &self,
+        good_states: &[usize],
+        infinitely_often: bool,
+    ) -> Option<Vec<usize>> {
+        let mut is_good = vec![false; self.states.len()];
+        for &state in good_states {
+            is_good[state] = true;
+        }
+        // The prefix may only visit good states if they need to be visited infinitely often
+        let prefix_allowed = |state: usize| infinitely_often || !is_good[state];
+
+        let mut parent = vec![None; self.states.len()];
+        let mut visited = vec![false; self.states.len()];
+        let mut queue = VecDeque::new();
+        if prefix_allowed(self.initial_state) {
+            visited[self.initial_state] = true;
+            queue.push_back(self.initial_state);
+        }
+        while let Some(state) = queue.pop_front() {
+            let suffix = if self.states[state].outgoing_transitions.is_empty() {
+                (!is_good[state]).then(Vec::new)
+            } else if is_good[state] {
+                None
+            } else {
+                self.cycle_avoiding(state, &is_good)
+            };
+            if let Some(suffix) = suffix {
+                let mut path = vec![state];
+                while let Some(previous) = parent[*path.last().unwrap()] {
+                    path.push(previous);
+                }
+                path.reverse();
+                path.extend(suffix);
+                return Some(path);
+            }
+
+            for transition in &self.states[state].outgoing_transitions {
+                let successor = transition.destination;
+                if !visited[successor] && prefix_allowed(successor) {
+                    visited[successor] = true;
+                    parent[successor] = Some(state);
+                    queue.push_back(successor);
+                }
+            }
+        }
+        None
+    }
+
+    // Returns the states of a shortest cycle from `start` back to `start` that visits no good state,
+    // excluding the first occurrence of `start`
+    fn cycle_avoiding(&self, start: usize, is_good: &[bool]) -> Option<Vec<usize>> {
+        let mut parent = vec![None; self.states.len()];
+        let mut queue = VecDeque::from([start]);
+        while let Some(state) = queue.pop_front() {
+            for transition in &self.states[state].outgoing_transitions {
+                let successor = transition.destination;
+                if successor == start {
+                    let mut cycle = vec![start, state];
+                    while let Some(previous) = parent[*cycle.last().unwrap()] {
+                        cycle.push(previous);
+                    }
+                    // The parents lead back to `start`, which is already the end of the prefix
+                    cycle.pop();
+                    cycle.reverse();
+                    return Some(cycle);
+                }
+                if parent[successor].is_none() && !is_good[successor] && successor != start {
+                    parent[successor] = Some(state);
+                    queue.push_back(successor);
+                }
+            }
+        }
+        None
+    }
+
+    // Checks that a counterexample violates the liveness property that the given good states are
+    // visited eventually (or, if `infinitely_often` is set, infinitely often), see
+    // `lasso_counterexample`
+    pub fn verify_lasso(
+        &self,
+        counterexample: &[usize],
+        good_states: &[usize],
+        infinitely_often: bool,
+    ) {
+        if counterexample.is_empty() {
             panic!("Counterexample must not be empty");
         }
         if counterexample[0] != self.initial_state {
@@ -64,19 +390,55 @@ impl TransitionSystem {
                 )
             }
         }
-        if !self.states[counterexample[counterexample.len() - 1]].is_bad {
-            panic!("Last state of counterexample is not labelled as bad state.")
+
+        let last = counterexample[counterexample.len() - 1];
+        let recurring_states = match counterexample[..counterexample.len() - 1]
+            .iter()
+            .position(|&state| state == last)
+        {
+            Some(cycle_start) => &counterexample[cycle_start..],
+            None if self.states[last].outgoing_transitions.is_empty() => &counterexample[counterexample.len() - 1..],
+            None => panic!("Last state of counterexample neither repeats an earlier state to close a cycle nor has no successors."),
+        };
+        let avoided_states = if infinitely_often {
+            recurring_states
+        } else {
+            counterexample
+        };
+        if let Some(&good_state) = avoided_states
+            .iter()
+            .find(|state| good_states.contains(state))
+        {
+            panic!(
+                "Counterexample visits the good state ({}){}.",
+                self.states[good_state].to_string(&self.variables),
+                if infinitely_often {
+                    " on its cycle"
+                } else {
+                    ""
+                }
+            )
         }
     }
-}
 
-impl Default for TransitionSystem {
-    fn default() -> Self {
-        Self {
-            states: Vec::new(),
-            initial_state: 0,
-            variables: Vec::new(),
-            label_names: Vec::new(),
+    pub fn verify_counterexample(&self, counterexample: &[usize]) {
+        if counterexample.is_empty() {
+            panic!("Counterexample must not be empty");
+        }
+        if counterexample[0] != self.initial_state {
+            panic!("Counterexample does not start in initial state");
+        }
+        for (&from, &to) in counterexample.iter().zip(counterexample.iter().skip(1)) {
+            if !self.states[from].has_transition_to(to) {
+                panic!(
+                    "Counterexample transition from ({}) to ({}) does not exist.",
+                    self.states[from].to_string(&self.variables),
+                    self.states[to].to_string(&self.variables)
+                )
+            }
+        }
+        if !self.states[counterexample[counterexample.len() - 1]].is_bad {
+            panic!("Last state of counterexample is not labelled as bad state.")
         }
     }
 }
@@ -98,7 +460,7 @@ impl State {
         }
     }
 
-    pub fn to_string(&self, variables: &Vec<Variable>) -> String {
+    pub fn to_string(&self, variables: &[Variable]) -> String {
         self.valuation_indices
             .iter()
             .zip(variables.iter())
@@ -116,11 +478,15 @@ impl State {
 
 pub struct Transition {
     pub destination: usize,
+    pub action: Option<String>, // The name of the action, if the model exports it
 }
 
 impl Transition {
-    pub fn new(destination: usize) -> Self {
-        Self { destination }
+    pub fn new(destination: usize, action: Option<String>) -> Self {
+        Self {
+            destination,
+            action,
+        }
     }
 }
 
@@ -155,3 +521,54 @@ impl Variable {
         None
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Both 1 and 2 lead to the bad state 4 in three steps, while the shorter path through 5 passes
+    // the other bad state 5
+    fn detour_system() -> TransitionSystem {
+        let successors = vec![
+            vec![1, 2, 5],
+            vec![0, 3],
+            vec![3],
+            vec![4],
+            vec![4],
+            vec![4],
+        ];
+        let mut ts = TransitionSystem::from_successors(&successors, &[]);
+        ts.states[4].is_bad = true;
+        ts.states[5].is_bad = true;
+        ts
+    }
+
+    #[test]
+    fn shorten_removes_loops() {
+        let ts = detour_system();
+        assert_eq!(
+            ts.shorten_counterexample(&[0, 1, 0, 1, 3, 4]),
+            vec![0, 1, 3, 4]
+        );
+    }
+
+    #[test]
+    fn shorten_stops_at_the_first_bad_state() {
+        // The bad state 2 can only be reached through the bad state 1, and 3 is a safe sink
+        let successors = vec![vec![1, 3], vec![2], vec![2], vec![3]];
+        let mut ts = TransitionSystem::from_successors(&successors, &[]);
+        ts.states[1].is_bad = true;
+        ts.states[2].is_bad = true;
+        assert_eq!(ts.shorten_counterexample(&[0, 1, 2]), vec![0, 1]);
+    }
+
+    #[test]
+    fn shorten_prefers_the_end_of_the_counterexample() {
+        let ts = detour_system();
+        assert_eq!(
+            ts.shorten_counterexample(&[0, 1, 0, 2, 3, 4]),
+            vec![0, 2, 3, 4]
+        );
+        assert_eq!(ts.shorten_counterexample(&[0, 2, 3, 4]), vec![0, 2, 3, 4]);
+    }
+}